@@ -51,4 +51,19 @@ export class Configuration {
 
         return Path.resolve(serverPath);
     }
+
+    /** The maximum number of results returned for queries that can produce a lot of them. */
+    get maxResults(): number {
+        return this.configuration.get<number>('maxResults', 1000);
+    }
+
+    /** Whether workspace symbol queries report test functions. */
+    get workspaceSymbolsIncludeTests(): boolean {
+        return this.configuration.get<boolean>('workspaceSymbolsIncludeTests', true);
+    }
+
+    /** Whether inlay hints show the names of the parameters that call arguments are passed to. */
+    get inlayHintsParameterNames(): boolean {
+        return this.configuration.get<boolean>('inlayHints.parameterNames', false);
+    }
 }
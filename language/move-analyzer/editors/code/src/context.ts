@@ -123,6 +123,11 @@ export class Context {
         const clientOptions: lc.LanguageClientOptions = {
             documentSelector: [{ scheme: 'file', language: 'move' }],
             traceOutputChannel,
+            initializationOptions: {
+                maxResults: this.configuration.maxResults,
+                workspaceSymbolsIncludeTests: this.configuration.workspaceSymbolsIncludeTests,
+                inlayHintsParameterNames: this.configuration.inlayHintsParameterNames,
+            },
         };
 
         const client = new lc.LanguageClient(
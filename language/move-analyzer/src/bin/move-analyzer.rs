@@ -4,29 +4,38 @@
 
 use anyhow::Result;
 use clap::Parser;
-use crossbeam::channel::{bounded, select};
+use crossbeam::channel::{after, bounded, never, select};
 use lsp_server::{Connection, Message, Notification, Request, Response};
 use lsp_types::{
-    notification::Notification as _, request::Request as _, CompletionOptions, Diagnostic,
-    HoverProviderCapability, OneOf, SaveOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
-    TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions,
+    notification::Notification as _, request::Request as _, CodeActionProviderCapability,
+    CompletionOptions, Diagnostic, FoldingRangeProviderCapability, HoverProviderCapability,
+    ImplementationProviderCapability, LinkedEditingRangeServerCapabilities, NumberOrString, OneOf,
+    SaveOptions, SemanticTokensFullOptions, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability,
+    WorkDoneProgressOptions,
 };
 use std::{
     collections::BTreeMap,
     path::Path,
     sync::{Arc, Mutex},
     thread,
+    time::Duration,
 };
 
 use move_analyzer::{
     completion::on_completion_request,
-    context::Context,
+    context::{Config, Context},
     symbols,
     vfs::{on_text_document_sync_notification, VirtualFileSystem},
 };
 use move_symbol_pool::Symbol;
 use url::Url;
 
+/// Time between scanning two files for a workspace symbol request, which keeps the main loop from
+/// spinning while a scan is in progress
+const SCAN_STEP_INTERVAL: Duration = Duration::from_millis(1);
+
 #[derive(Parser)]
 #[clap(author, version, about)]
 struct Options {}
@@ -54,6 +63,7 @@ fn main() {
         connection,
         files: VirtualFileSystem::default(),
         symbols: symbols.clone(),
+        config: Config::default(),
     };
 
     let (id, client_response) = context
@@ -61,7 +71,7 @@ fn main() {
         .initialize_start()
         .expect("could not start connection initialization");
 
-    let capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
+    let mut capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
         // The server receives notifications from the client as users open, close,
         // and modify documents.
         text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -105,19 +115,51 @@ fn main() {
         type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(
             symbols::DEFS_AND_REFS_SUPPORT,
         )),
+        implementation_provider: Some(ImplementationProviderCapability::Simple(
+            symbols::DEFS_AND_REFS_SUPPORT,
+        )),
         references_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
         document_symbol_provider: Some(OneOf::Left(true)),
+        document_highlight_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(
+            symbols::DEFS_AND_REFS_SUPPORT,
+        )),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+                legend: symbols::semantic_tokens_legend(),
+                range: None,
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+            },
+        )),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
         ..Default::default()
     })
     .expect("could not serialize server capabilities");
+    // inlay hints were introduced in LSP 3.17, which `lsp_types` 0.90 predates, so
+    // `ServerCapabilities` has no field to advertise them and they are added to the serialized
+    // capabilities instead
+    capabilities["inlayHintProvider"] = serde_json::Value::Bool(true);
+
+    let initialize_params: lsp_types::InitializeParams =
+        serde_json::from_value(client_response).expect("could not deserialize client capabilities");
+    context.config = Config::from_initialization_options(initialize_params.initialization_options);
 
     let (diag_sender, diag_receiver) = bounded::<Result<BTreeMap<Symbol, Vec<Diagnostic>>>>(0);
     let mut symbolicator_runner = symbols::SymbolicatorRunner::idle();
     if symbols::DEFS_AND_REFS_SUPPORT {
-        let initialize_params: lsp_types::InitializeParams =
-            serde_json::from_value(client_response)
-                .expect("could not deserialize client capabilities");
-
         symbolicator_runner = symbols::SymbolicatorRunner::new(symbols.clone(), diag_sender);
 
         // If initialization information from the client contains a path to the directory being
@@ -156,7 +198,17 @@ fn main() {
         )
         .expect("could not finish connection initialization");
 
+    // workspace symbol request whose (potentially long) scan is in progress
+    let mut workspace_symbol_scan: Option<symbols::WorkspaceSymbolScan> = None;
     loop {
+        // while a workspace symbol scan is in progress, it advances one file at a time so that
+        // messages arriving in the meantime (e.g., cancellations or newer queries) are processed
+        // without waiting for the whole scan to complete
+        let scan_tick = if workspace_symbol_scan.is_some() {
+            after(SCAN_STEP_INTERVAL)
+        } else {
+            never()
+        };
         select! {
             recv(diag_receiver) -> message => {
                 match message {
@@ -197,21 +249,54 @@ fn main() {
             },
             recv(context.connection.receiver) -> message => {
                 match message {
+                    Ok(Message::Request(request))
+                        if request.method == lsp_types::request::WorkspaceSymbol::METHOD =>
+                    {
+                        // a new query supersedes the one being currently processed
+                        if let Some(scan) = workspace_symbol_scan.take() {
+                            scan.cancel(&context);
+                        }
+                        workspace_symbol_scan = Some(symbols::WorkspaceSymbolScan::new(
+                            &request,
+                            &context.symbols.lock().unwrap(),
+                            &context.config,
+                        ));
+                    }
                     Ok(Message::Request(request)) => on_request(&context, &request),
                     Ok(Message::Response(response)) => on_response(&context, &response),
                     Ok(Message::Notification(notification)) => {
                         match notification.method.as_str() {
                             lsp_types::notification::Exit::METHOD => break,
                             lsp_types::notification::Cancel::METHOD => {
-                                // TODO: Currently the server does not implement request cancellation.
-                                // It ought to, especially once it begins processing requests that may
-                                // take a long time to respond to.
+                                // Only workspace symbol requests are processed incrementally and can
+                                // be cancelled, all other requests are responded to immediately.
+                                match serde_json::from_value::<lsp_types::CancelParams>(
+                                    notification.params,
+                                ) {
+                                    Ok(parameters) => {
+                                        let id = match parameters.id {
+                                            NumberOrString::Number(n) => n.into(),
+                                            NumberOrString::String(s) => s.into(),
+                                        };
+                                        if workspace_symbol_scan.as_ref().map(|scan| scan.id()) == Some(&id) {
+                                            workspace_symbol_scan.take().unwrap().cancel(&context);
+                                        }
+                                    }
+                                    Err(err) => eprintln!("could not deserialize cancel request: {:?}", err),
+                                }
                             }
                             _ => on_notification(&mut context, &symbolicator_runner, &notification),
                         }
                     }
                     Err(error) => eprintln!("IDE message error: {:?}", error),
                 }
+            },
+            recv(scan_tick) -> _ => {
+                if let Some(scan) = workspace_symbol_scan.as_mut() {
+                    if scan.step(&context, &context.symbols.lock().unwrap()) {
+                        workspace_symbol_scan = None;
+                    }
+                }
             }
         };
     }
@@ -232,15 +317,57 @@ fn on_request(context: &Context, request: &Request) {
         lsp_types::request::GotoTypeDefinition::METHOD => {
             symbols::on_go_to_type_def_request(context, request, &context.symbols.lock().unwrap());
         }
+        lsp_types::request::GotoImplementation::METHOD => {
+            symbols::on_go_to_impl_request(context, request, &context.symbols.lock().unwrap());
+        }
         lsp_types::request::References::METHOD => {
             symbols::on_references_request(context, request, &context.symbols.lock().unwrap());
         }
+        lsp_types::request::DocumentHighlightRequest::METHOD => {
+            symbols::on_document_highlight_request(
+                context,
+                request,
+                &context.symbols.lock().unwrap(),
+            );
+        }
+        lsp_types::request::LinkedEditingRange::METHOD => {
+            symbols::on_linked_editing_range_request(
+                context,
+                request,
+                &context.symbols.lock().unwrap(),
+            );
+        }
         lsp_types::request::HoverRequest::METHOD => {
             symbols::on_hover_request(context, request, &context.symbols.lock().unwrap());
         }
         lsp_types::request::DocumentSymbolRequest::METHOD => {
             symbols::on_document_symbol_request(context, request, &context.symbols.lock().unwrap());
         }
+        lsp_types::request::SemanticTokensFullRequest::METHOD => {
+            symbols::on_semantic_tokens_full_request(
+                context,
+                request,
+                &context.symbols.lock().unwrap(),
+            );
+        }
+        lsp_types::request::CodeActionRequest::METHOD => {
+            symbols::on_code_action_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::SignatureHelpRequest::METHOD => {
+            symbols::on_signature_help_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::Rename::METHOD => {
+            symbols::on_rename_request(context, request, &context.symbols.lock().unwrap());
+        }
+        lsp_types::request::FoldingRangeRequest::METHOD => {
+            symbols::on_folding_range_request(context, request, &context.symbols.lock().unwrap());
+        }
+        symbols::InlayHintRequest::METHOD => {
+            symbols::on_inlay_hint_request(context, request, &context.symbols.lock().unwrap());
+        }
+        symbols::PeekDefinitionRequest::METHOD => {
+            symbols::on_peek_definition_request(context, request, &context.symbols.lock().unwrap());
+        }
         _ => eprintln!("handle request '{}' from client", request.method),
     }
 }
@@ -2,7 +2,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, symbols::Symbols};
+use crate::{context::Context, symbols::Symbols, vfs::VirtualFileSystem};
 use lsp_server::Request;
 use lsp_types::{CompletionItem, CompletionItemKind, CompletionParams, Position};
 use move_command_line_common::files::FileHash;
@@ -11,7 +11,10 @@ use move_compiler::parser::{
     lexer::{Lexer, Tok},
 };
 use move_symbol_pool::Symbol;
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
 /// Constructs an `lsp_types::CompletionItem` with the given `label` and `kind`.
 fn completion_item(label: &str, kind: CompletionItemKind) -> CompletionItem {
@@ -60,6 +63,39 @@ fn builtins() -> Vec<CompletionItem> {
         .collect()
 }
 
+/// Return a list of completion items for the definitions in scope at the given position of the
+/// Move source file at the given path: the locals in scope there in the enclosing function
+/// (with their types as detail), and the functions (with their signatures as detail) and structs
+/// of the modules in the file.
+fn scope_items(
+    symbols: &Symbols,
+    files: &VirtualFileSystem,
+    path: &Path,
+    position: &Position,
+) -> Vec<CompletionItem> {
+    let mut items: Vec<_> = symbols
+        .local_defs(files, path, position)
+        .into_iter()
+        .map(|(name, ident_type)| CompletionItem {
+            detail: Some(ident_type.to_string()),
+            ..completion_item(&name, CompletionItemKind::Variable)
+        })
+        .collect();
+
+    for mod_defs in symbols.file_mods().get(path).into_iter().flatten() {
+        for (name, fun_def) in mod_defs.functions() {
+            items.push(CompletionItem {
+                detail: Some(fun_def.ident_type().to_string()),
+                ..completion_item(name, CompletionItemKind::Function)
+            });
+        }
+        for name in mod_defs.struct_names() {
+            items.push(completion_item(name, CompletionItemKind::Struct));
+        }
+    }
+    items
+}
+
 /// Lexes the Move source file at the given path and returns a list of completion items
 /// corresponding to the non-keyword identifiers therein.
 ///
@@ -182,12 +218,24 @@ pub fn on_completion_request(context: &Context, request: &Request, symbols: &Sym
             // offer them Move's keywords, operators, and builtins as completion items.
             items.extend_from_slice(&keywords());
             items.extend_from_slice(&builtins());
+            items.extend(scope_items(
+                symbols,
+                &context.files,
+                &path,
+                &parameters.text_document_position.position,
+            ));
         }
     }
 
     if let Some(buffer) = &buffer {
+        // skip identifiers already suggested based on the definitions in scope
+        let labels: HashSet<_> = items.iter().map(|item| item.label.clone()).collect();
         let identifiers = identifiers(buffer, symbols, &path);
-        items.extend_from_slice(&identifiers);
+        items.extend(
+            identifiers
+                .into_iter()
+                .filter(|item| !labels.contains(&item.label)),
+        );
     }
 
     let result = serde_json::to_value(items).expect("could not serialize completion response");
@@ -201,3 +249,66 @@ pub fn on_completion_request(context: &Context, request: &Request, symbols: &Sym
         eprintln!("could not send completion response: {:?}", err);
     }
 }
+
+#[test]
+/// Tests if completions inside a function body include a local declared in it and a module-level
+/// function.
+fn scope_items_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = crate::symbols::Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    // at the last use of `tmp` in the mut function
+    let files = VirtualFileSystem::default();
+    let items = scope_items(&symbols, &files, &cpath, &Position::new(58, 8));
+
+    let local = items.iter().find(|item| item.label == "tmp").unwrap();
+    assert_eq!(local.kind, Some(CompletionItemKind::Variable));
+    assert_eq!(local.detail.as_deref(), Some("u64"));
+
+    let fun = items.iter().find(|item| item.label == "cp").unwrap();
+    assert_eq!(fun.kind, Some(CompletionItemKind::Function));
+    assert_eq!(
+        fun.detail.as_deref(),
+        Some("fun Symbols::M1::cp(value: u64): u64")
+    );
+
+    // locals of other functions are not in scope
+    assert!(items.iter().all(|item| item.label != "value"));
+}
+
+#[test]
+/// Tests if completions exclude locals of blocks closed before the cursor and locals shadowed by
+/// later definitions with the same name.
+fn scope_items_blocks_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = crate::symbols::Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M13.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let files = VirtualFileSystem::default();
+    let labels = |line, col| -> Vec<String> {
+        scope_items(&symbols, &files, &cpath, &Position::new(line, col))
+            .into_iter()
+            .filter(|item| item.kind == Some(CompletionItemKind::Variable))
+            .map(|item| item.label)
+            .collect()
+    };
+
+    // at the end of the block, where `a` is shadowed
+    assert_eq!(labels(6, 12), vec!["p", "b", "a"]);
+    // after the block, where `b` is no longer in scope
+    assert_eq!(labels(9, 8), vec!["p", "a", "c"]);
+}
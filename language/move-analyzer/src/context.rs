@@ -4,8 +4,13 @@
 
 use crate::{symbols::Symbols, vfs::VirtualFileSystem};
 use lsp_server::Connection;
+use serde::Deserialize;
 use std::sync::{Arc, Mutex};
 
+/// Default maximum number of results returned for queries that can potentially produce a lot of
+/// them (e.g., references of a widely used symbol).
+pub const DEFAULT_MAX_RESULTS: usize = 1000;
+
 /// The context within which the language server is running.
 pub struct Context {
     /// The connection with the language server's client.
@@ -14,4 +19,46 @@ pub struct Context {
     pub files: VirtualFileSystem,
     /// Symbolication information
     pub symbols: Arc<Mutex<Symbols>>,
+    /// Configuration provided by the client
+    pub config: Config,
+}
+
+/// Language server configuration, provided by the client in the initialization options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    /// Maximum number of results returned for references, workspace symbol, and document highlight
+    /// queries (0 means that the number of results is unlimited)
+    pub max_results: usize,
+    /// Whether workspace symbol queries report test functions (i.e., functions annotated with
+    /// `#[test]` or `#[test_only]`)
+    pub workspace_symbols_include_tests: bool,
+    /// Whether inlay hints show the names of the parameters that call arguments are passed to
+    pub inlay_hints_parameter_names: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_results: DEFAULT_MAX_RESULTS,
+            workspace_symbols_include_tests: true,
+            inlay_hints_parameter_names: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads configuration from the client's initialization options, falling back to the default
+    /// configuration if they are absent or malformed.
+    pub fn from_initialization_options(options: Option<serde_json::Value>) -> Self {
+        options
+            .and_then(|options| match serde_json::from_value(options) {
+                Ok(config) => Some(config),
+                Err(err) => {
+                    eprintln!("could not deserialize initialization options: {:?}", err);
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
 }
@@ -47,25 +47,36 @@
 //! matching uses to a definition in the innermost scope.
 
 use crate::{
-    context::Context,
+    context::{Config, Context},
     diagnostics::{lsp_diagnostics, lsp_empty_diagnostics},
     utils::get_loc,
+    vfs::VirtualFileSystem,
 };
 use anyhow::{anyhow, Result};
 use codespan_reporting::files::SimpleFiles;
 use crossbeam::channel::Sender;
 use derivative::*;
 use im::ordmap::OrdMap;
-use lsp_server::{Request, RequestId};
+use lsp_server::{ErrorCode, Notification, Request, RequestId};
 use lsp_types::{
-    request::GotoTypeDefinitionParams, Diagnostic, DocumentSymbol, DocumentSymbolParams,
-    GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString, Location,
-    MarkedString, Position, Range, ReferenceParams, SymbolKind,
+    notification::{Notification as _, Progress, ShowMessage},
+    request::{GotoImplementationParams, GotoTypeDefinitionParams},
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic,
+    DocumentHighlight, DocumentHighlightParams, DocumentSymbol, DocumentSymbolParams, FoldingRange,
+    FoldingRangeParams, GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString,
+    LinkedEditingRangeParams, LinkedEditingRanges, Location, MarkedString, MessageType,
+    ParameterInformation, ParameterLabel, Position, ProgressToken, Range, ReferenceParams,
+    RenameParams, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, ShowMessageParams, SignatureHelp,
+    SignatureHelpParams, SignatureInformation, SymbolInformation, SymbolKind,
+    TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, WorkspaceEdit,
+    WorkspaceSymbolParams,
 };
+use serde::{Deserialize, Serialize};
 
 use std::{
     cmp,
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     fmt,
     path::{Path, PathBuf},
     sync::{Arc, Condvar, Mutex},
@@ -76,15 +87,18 @@ use url::Url;
 
 use move_command_line_common::files::FileHash;
 use move_compiler::{
-    expansion::ast::{Address, Fields, ModuleIdent, ModuleIdent_},
+    expansion::ast::{AbilitySet, Address, Fields, ModuleIdent, ModuleIdent_},
     naming::ast::{StructDefinition, StructFields, TParam, Type, TypeName_, Type_},
-    parser::ast::StructName,
-    shared::Identifier,
+    parser::{
+        ast::{self as P, StructName},
+        keywords::KEYWORDS,
+    },
+    shared::{known_attributes::TestingAttribute, unique_map::UniqueMap, Identifier, Name},
     typing::ast::{
         BuiltinFunction_, Exp, ExpListItem, Function, FunctionBody_, LValue, LValueList, LValue_,
         ModuleCall, ModuleDefinition, SequenceItem, SequenceItem_, UnannotatedExp_,
     },
-    PASS_TYPING,
+    PASS_PARSER, PASS_TYPING,
 };
 use move_ir_types::location::*;
 use move_package::compilation::build_plan::BuildPlan;
@@ -151,6 +165,9 @@ pub struct UseDef {
     type_def_loc: Option<DefLoc>,
     /// Doc string for the relevant identifier/function
     doc_string: String,
+    /// Description of the identifier displayed on hover instead of its type (e.g., of the aliased
+    /// definition if the identifier is part of a `use` declaration)
+    description: Option<String>,
 }
 
 /// Definition of a struct field
@@ -172,6 +189,8 @@ struct StructDef {
 pub struct FunctionDef {
     name: Symbol,
     start: Position,
+    /// Start of the function's body (`None` for native functions)
+    body_start: Option<Position>,
     attrs: Vec<String>,
     #[derivative(PartialOrd = "ignore")]
     #[derivative(Ord = "ignore")]
@@ -193,6 +212,8 @@ pub struct ModuleDefs {
     constants: BTreeMap<Symbol, Position>,
     /// Function definitions
     functions: BTreeMap<Symbol, FunctionDef>,
+    /// Spec schema definitions
+    schemas: BTreeMap<Symbol, Position>,
 }
 
 /// Data used during symbolication
@@ -209,6 +230,11 @@ pub struct Symbolicator {
     type_params: BTreeMap<Symbol, DefLoc>,
     /// Current processed module (always set before module processing starts)
     current_mod: Option<ModuleIdent>,
+    /// End of the innermost block enclosing the currently processed expression (set when
+    /// processing a function)
+    block_end: Option<Position>,
+    /// Ends of the scopes (i.e., of the enclosing blocks) of local definitions
+    local_scope_ends: BTreeMap<DefLoc, Position>,
 }
 
 /// Maps a line number to a list of use-def pairs on a given line (use-def set is sorted by
@@ -230,6 +256,12 @@ pub struct Symbols {
     file_name_mapping: BTreeMap<FileHash, Symbol>,
     /// A mapping from filePath to ModuleDefs
     file_mods: BTreeMap<PathBuf, BTreeSet<ModuleDefs>>,
+    /// A mapping from filePath to the ranges of lines that can be folded in the file
+    file_folding_ranges: BTreeMap<PathBuf, Vec<FoldingRange>>,
+    /// Hashes of the files of dependency packages (which must not be modified, e.g., by renaming)
+    dependency_files: BTreeSet<FileHash>,
+    /// Ends of the scopes (i.e., of the enclosing blocks) of local definitions
+    local_scope_ends: BTreeMap<DefLoc, Position>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -248,6 +280,16 @@ impl ModuleDefs {
     pub fn functions(&self) -> &BTreeMap<Symbol, FunctionDef> {
         &self.functions
     }
+
+    pub fn struct_names(&self) -> impl Iterator<Item = &Symbol> {
+        self.structs.keys()
+    }
+}
+
+impl FunctionDef {
+    pub fn ident_type(&self) -> &IdentType {
+        &self.ident_type
+    }
 }
 
 impl fmt::Display for IdentType {
@@ -356,6 +398,80 @@ fn addr_to_ide_string(addr: &Address) -> String {
     }
 }
 
+fn mod_to_ide_string(mod_ident: &ModuleIdent_) -> String {
+    format!(
+        "{}::{}",
+        addr_to_ide_string(&mod_ident.address),
+        mod_ident.module.value()
+    )
+}
+
+/// Full declaration of a struct, including its type parameters, abilities and fields, formatted as
+/// Move source (e.g., for display on hover)
+fn struct_to_ide_string(mod_ident: &ModuleIdent_, name: &Symbol, def: &StructDefinition) -> String {
+    let type_params = if def.type_parameters.is_empty() {
+        "".to_string()
+    } else {
+        let params = def
+            .type_parameters
+            .iter()
+            .map(|stp| {
+                let mut s = if stp.is_phantom {
+                    "phantom ".to_string()
+                } else {
+                    "".to_string()
+                };
+                s.push_str(stp.param.user_specified_name.value.as_str());
+                s.push_str(&abilities_to_ide_string(&stp.param.abilities, ": ", " + "));
+                s
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("<{}>", params)
+    };
+
+    let header = format!(
+        "struct {}::{}{}{}",
+        mod_to_ide_string(mod_ident),
+        name,
+        type_params,
+        abilities_to_ide_string(&def.abilities, " has ", ", "),
+    );
+
+    match &def.fields {
+        StructFields::Native(_) => format!("native {};", header),
+        StructFields::Defined(fields) => {
+            let mut fields: Vec<_> = fields
+                .iter()
+                .map(|(_, fname, (idx, t))| (*idx, fname, t))
+                .collect();
+            fields.sort_by_key(|(idx, _, _)| *idx);
+            if fields.is_empty() {
+                return format!("{} {{}}", header);
+            }
+            let fields = fields
+                .iter()
+                .map(|(_, fname, t)| format!("    {}: {},\n", fname, type_to_ide_string(t)))
+                .collect::<String>();
+            format!("{} {{\n{}}}", header, fields)
+        }
+    }
+}
+
+/// Abilities in `abilities` separated by `sep` and preceded by `prefix`, or nothing if there are no
+/// abilities
+fn abilities_to_ide_string(abilities: &AbilitySet, prefix: &str, sep: &str) -> String {
+    if abilities.is_empty() {
+        return "".to_string();
+    }
+    let abilities = abilities
+        .iter()
+        .map(|sp!(_, a)| a.to_string())
+        .collect::<Vec<_>>()
+        .join(sep);
+    format!("{}{}", prefix, abilities)
+}
+
 fn type_list_to_ide_string(types: &[Type]) -> String {
     types
         .iter()
@@ -532,8 +648,14 @@ impl UseDef {
             def_loc,
             type_def_loc,
             doc_string,
+            description: None,
         }
     }
+
+    fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
 }
 
 impl Ord for UseDef {
@@ -601,11 +723,101 @@ impl Symbols {
         self.file_use_defs.extend(other.file_use_defs);
         self.file_name_mapping.extend(other.file_name_mapping);
         self.file_mods.extend(other.file_mods);
+        self.file_folding_ranges.extend(other.file_folding_ranges);
+        self.dependency_files.extend(other.dependency_files);
+        self.local_scope_ends.extend(other.local_scope_ends);
     }
 
     pub fn file_mods(&self) -> &BTreeMap<PathBuf, BTreeSet<ModuleDefs>> {
         &self.file_mods
     }
+
+    /// Names and types of the local definitions (parameters, type parameters and local variables)
+    /// in scope at `pos` in the function enclosing it, in the file at `path` (read from `files` if
+    /// it is open in the editor). Definitions in blocks closed before `pos`, and definitions
+    /// shadowed by later ones with the same name, are not in scope.
+    pub fn local_defs(
+        &self,
+        files: &VirtualFileSystem,
+        path: &Path,
+        pos: &Position,
+    ) -> Vec<(Symbol, IdentType)> {
+        let (Some(mods), Some(use_defs)) = (self.file_mods.get(path), self.file_use_defs.get(path))
+        else {
+            return vec![];
+        };
+
+        // the enclosing function is the last module member starting before `pos` (if it is a
+        // function)
+        let mut enclosing: Option<(Position, bool, FileHash)> = None;
+        for mod_defs in mods {
+            let fun_starts = mod_defs.functions.values().map(|f| (f.start, true));
+            let other_starts = mod_defs
+                .structs
+                .values()
+                .map(|s| s.name_start)
+                .chain(mod_defs.constants.values().copied())
+                .chain(mod_defs.schemas.values().copied())
+                .map(|start| (start, false));
+            for (start, is_fun) in fun_starts.chain(other_starts) {
+                if start <= *pos && enclosing.map_or(true, |(s, _, _)| s < start) {
+                    enclosing = Some((start, is_fun, mod_defs.fhash));
+                }
+            }
+        }
+        let Some((fun_start, true, fhash)) = enclosing else {
+            return vec![];
+        };
+
+        let Ok(contents) = file_contents(files, &path.to_path_buf()) else {
+            return vec![];
+        };
+        let lines: Vec<_> = contents.lines().collect();
+
+        let mut locals: Vec<(Symbol, IdentType)> = vec![];
+        for line in fun_start.line..=pos.line {
+            for u in use_defs.get(line).into_iter().flatten() {
+                let start = Position::new(line, u.col_start);
+                let is_decl = u.def_loc.fhash == fhash && u.def_loc.start == start;
+                let in_scope = self
+                    .local_scope_ends
+                    .get(&u.def_loc)
+                    .map_or(true, |scope_end| pos < scope_end);
+                if is_decl && in_scope && start != fun_start && start < *pos {
+                    let name = Symbol::from(use_name(&lines, line, &u));
+                    locals.retain(|(local_name, _)| *local_name != name);
+                    locals.push((name, u.use_type));
+                }
+            }
+        }
+        locals
+    }
+
+    /// Locations of the definitions of the identifiers at `positions` (line and column pairs) in
+    /// the file at `path`, in the order of `positions` (`None` where there is no identifier with a
+    /// known definition). All positions are looked up in a single pass over the file's identifiers.
+    pub fn def_locations(&self, path: &Path, positions: &[(u32, u32)]) -> Vec<Option<Location>> {
+        let mut locations = vec![None; positions.len()];
+        let Some(use_defs) = self.file_use_defs.get(path) else {
+            return locations;
+        };
+
+        let mut by_line: BTreeMap<u32, Vec<(u32, usize)>> = BTreeMap::new();
+        for (i, (line, col)) in positions.iter().enumerate() {
+            by_line.entry(*line).or_default().push((*col, i));
+        }
+
+        for (line, cols) in by_line {
+            for u in use_defs.0.get(&line).into_iter().flatten() {
+                for (col, i) in &cols {
+                    if *col >= u.col_start && *col <= u.col_end {
+                        locations[*i] = Some(def_ide_location(self, &u.def_loc));
+                    }
+                }
+            }
+        }
+        locations
+    }
 }
 
 impl Symbolicator {
@@ -644,12 +856,40 @@ impl Symbolicator {
             file_id_to_lines.insert(id, lines);
         }
 
+        let root_package = resolution_graph.root_package.package.name;
+        let mut dependency_file_names = BTreeSet::new();
+        for (pkg_name, pkg) in &resolution_graph.package_table {
+            if *pkg_name != root_package {
+                dependency_file_names.extend(pkg.get_sources(&resolution_graph.build_options)?);
+            }
+        }
+        let dependency_files = file_name_mapping
+            .iter()
+            .filter(|(_, fname)| dependency_file_names.contains(*fname))
+            .map(|(fhash, _)| *fhash)
+            .collect();
+
         let build_plan = BuildPlan::create(resolution_graph)?;
         let mut typed_ast = None;
         let mut diagnostics = None;
+        let mut parsed_ast = None;
         build_plan.compile_with_driver(&mut std::io::sink(), None, |compiler| {
-            let (files, compilation_result) = compiler.run::<PASS_TYPING>()?;
+            let (files, compilation_result) = compiler.run::<PASS_PARSER>()?;
             let (_, compiler) = match compilation_result {
+                Ok(v) => v,
+                Err(diags) => {
+                    let failure = true;
+                    diagnostics = Some((diags, failure));
+                    eprintln!("parsed AST compilation failed");
+                    return Ok((files, vec![]));
+                }
+            };
+            // `use` declarations are not retained past expansion so the parsed AST is needed to
+            // symbolicate them
+            let (compiler, parsed_program) = compiler.into_ast();
+            parsed_ast = Some(parsed_program.clone());
+            let compilation_result = compiler.at_parser(parsed_program).run::<PASS_TYPING>();
+            let compiler = match compilation_result {
                 Ok(v) => v,
                 Err(diags) => {
                     let failure = true;
@@ -703,6 +943,7 @@ impl Symbolicator {
         }
 
         let modules = &typed_ast.unwrap().modules;
+        let parsed_mods = Self::get_parsed_mods(&parsed_ast.unwrap());
 
         let mut mod_outer_defs = BTreeMap::new();
         let mut mod_use_defs = BTreeMap::new();
@@ -713,6 +954,7 @@ impl Symbolicator {
                 &pos,
                 &sp(pos, *module_ident),
                 module_def,
+                parsed_mods.get(&pos),
                 &files,
                 &file_id_mapping,
             );
@@ -740,10 +982,13 @@ impl Symbolicator {
             file_id_to_lines,
             type_params: BTreeMap::new(),
             current_mod: None,
+            block_end: None,
+            local_scope_ends: BTreeMap::new(),
         };
 
         let mut references = BTreeMap::new();
         let mut file_use_defs = BTreeMap::new();
+        let mut file_folding_ranges = BTreeMap::new();
         let mut function_ident_type = FunctionIdentTypeMap::new();
 
         for (pos, module_ident, module_def) in modules {
@@ -755,6 +1000,17 @@ impl Symbolicator {
                 &mut use_defs,
                 &mut function_ident_type,
             );
+            let mut folding_ranges = vec![];
+            if let Some(parsed_mod_def) = parsed_mods.get(&pos) {
+                folding_ranges = symbolicator.folding_ranges(parsed_mod_def);
+                symbolicator.use_decl_symbols(
+                    parsed_mod_def,
+                    modules,
+                    &mut references,
+                    &mut use_defs,
+                );
+                symbolicator.spec_symbols(parsed_mod_def, &mut references, &mut use_defs);
+            }
 
             let fpath = match source_files.get(&pos.file_hash()) {
                 Some((p, _)) => p,
@@ -764,6 +1020,11 @@ impl Symbolicator {
             let fpath_buffer = dunce::canonicalize(fpath.as_str())
                 .unwrap_or_else(|_| PathBuf::from(fpath.as_str()));
 
+            file_folding_ranges
+                .entry(fpath_buffer.clone())
+                .or_insert_with(Vec::new)
+                .extend(folding_ranges);
+
             file_use_defs
                 .entry(fpath_buffer)
                 .or_insert_with(UseDefMap::new)
@@ -775,6 +1036,9 @@ impl Symbolicator {
             file_use_defs,
             file_name_mapping,
             file_mods,
+            file_folding_ranges,
+            dependency_files,
+            local_scope_ends: symbolicator.local_scope_ends,
         };
 
         eprintln!("get_symbols load complete");
@@ -789,16 +1053,37 @@ impl Symbolicator {
             references: BTreeMap::new(),
             file_name_mapping: BTreeMap::new(),
             file_mods: BTreeMap::new(),
+            file_folding_ranges: BTreeMap::new(),
+            dependency_files: BTreeSet::new(),
+            local_scope_ends: BTreeMap::new(),
         }
     }
 
     /// Main AST traversal functions
 
-    /// Get symbols for outer definitions in the module (functions, structs, and consts)
+    /// Get all modules in the parsed program, keyed by the location of the module's name (which is
+    /// also the location of the module in the typed AST)
+    fn get_parsed_mods(parsed_program: &P::Program) -> HashMap<Loc, P::ModuleDefinition> {
+        parsed_program
+            .source_definitions
+            .iter()
+            .chain(parsed_program.lib_definitions.iter())
+            .flat_map(|pkg_def| match &pkg_def.def {
+                P::Definition::Module(mod_def) => vec![mod_def],
+                P::Definition::Address(addr_def) => addr_def.modules.iter().collect(),
+                P::Definition::Script(_) => vec![],
+            })
+            .map(|mod_def| (mod_def.name.loc(), mod_def.clone()))
+            .collect()
+    }
+
+    /// Get symbols for outer definitions in the module (functions, structs, consts, and spec
+    /// schemas)
     fn get_mod_outer_defs(
         loc: &Loc,
         mod_ident: &ModuleIdent,
         mod_def: &ModuleDefinition,
+        parsed_mod_def: Option<&P::ModuleDefinition>,
         files: &SimpleFiles<Symbol, String>,
         file_id_mapping: &HashMap<FileHash, usize>,
     ) -> (ModuleDefs, UseDefMap) {
@@ -886,11 +1171,18 @@ impl Symbolicator {
                     .map(|(k, v)| Self::create_struct_type(*mod_ident, *k, *v, vec![]))
                     .collect(),
             );
+            let body_start = match &fun.body.value {
+                FunctionBody_::Defined(_) => {
+                    Self::get_start_loc(&fun.body.loc, files, file_id_mapping)
+                }
+                FunctionBody_::Native => None,
+            };
             functions.insert(
                 *name,
                 FunctionDef {
                     name: *name,
                     start: name_start,
+                    body_start,
                     attrs: fun
                         .attributes
                         .clone()
@@ -902,6 +1194,29 @@ impl Symbolicator {
             );
         }
 
+        // spec schemas are not retained in the typed AST
+        let mut schemas = BTreeMap::new();
+        for member in parsed_mod_def
+            .into_iter()
+            .flat_map(|mod_def| &mod_def.members)
+        {
+            let name = match member {
+                P::ModuleMember::Spec(sp!(_, spec_block)) => match &spec_block.target.value {
+                    P::SpecBlockTarget_::Schema(name, _) => name,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let name_start = match Self::get_start_loc(&name.loc, files, file_id_mapping) {
+                Some(s) => s,
+                None => {
+                    debug_assert!(false);
+                    continue;
+                }
+            };
+            schemas.insert(name.value, name_start);
+        }
+
         let use_def_map = UseDefMap::new();
 
         let name = mod_ident.value;
@@ -921,6 +1236,7 @@ impl Symbolicator {
                         structs,
                         constants,
                         functions,
+                        schemas,
                     },
                     use_def_map,
                 );
@@ -934,6 +1250,7 @@ impl Symbolicator {
             structs,
             constants,
             functions,
+            schemas,
         };
 
         (module_defs, use_def_map)
@@ -1009,6 +1326,8 @@ impl Symbolicator {
                 vec![],
             ));
             let ident_type_def = self.ident_type_def_loc(&ident_type);
+            let description =
+                struct_to_ide_string(&self.current_mod.unwrap().value, name, struct_def);
             use_defs.insert(
                 name_start.line,
                 UseDef::new(
@@ -1021,13 +1340,396 @@ impl Symbolicator {
                     ident_type,
                     ident_type_def,
                     doc_string,
-                ),
+                )
+                .with_description(description),
             );
 
             self.struct_symbols(struct_def, references, use_defs);
         }
     }
 
+    /// Get symbols for `use` declarations in a module (both the used name and its alias, if any,
+    /// are treated as uses of the aliased definition)
+    fn use_decl_symbols(
+        &self,
+        parsed_mod_def: &P::ModuleDefinition,
+        modules: &UniqueMap<ModuleIdent, ModuleDefinition>,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        for member in &parsed_mod_def.members {
+            let use_decl = match member {
+                P::ModuleMember::Use(use_decl) => use_decl,
+                _ => continue,
+            };
+            match &use_decl.use_ {
+                P::Use::Module(mod_ident, alias_opt) => {
+                    let name = mod_ident.value.module.0;
+                    let alias = alias_opt.map(|a| a.0);
+                    self.add_mod_alias_use_defs(mod_ident, name, alias, references, use_defs);
+                }
+                P::Use::Members(mod_ident, members) => {
+                    for (member, alias_opt) in members {
+                        if member.value.as_str() == P::ModuleName::SELF_NAME {
+                            self.add_mod_alias_use_defs(
+                                mod_ident, *member, *alias_opt, references, use_defs,
+                            );
+                        } else {
+                            self.add_member_alias_use_defs(
+                                mod_ident, *member, *alias_opt, modules, references, use_defs,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add uses of a module in a `use` declaration
+    fn add_mod_alias_use_defs(
+        &self,
+        mod_ident: &P::ModuleIdent,
+        name: Name,
+        alias_opt: Option<Name>,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        let mod_defs = match self.use_mod_defs(mod_ident) {
+            Some(v) => v,
+            None => return,
+        };
+        let path = mod_to_ide_string(&mod_defs.name);
+        let alias = format!(
+            "alias {} = {}\nmodule {}",
+            alias_opt.unwrap_or(mod_ident.value.module.0),
+            path,
+            path
+        );
+        // modules do not have a type
+        let ident_type = IdentType::RegularType(sp(name.loc, Type_::Unit));
+        self.add_alias_use_defs(
+            name,
+            alias_opt,
+            mod_defs.fhash,
+            mod_defs.start,
+            ident_type,
+            alias,
+            references,
+            use_defs,
+        );
+    }
+
+    /// Add uses of a module member (struct, function, or constant) in a `use` declaration
+    fn add_member_alias_use_defs(
+        &self,
+        mod_ident: &P::ModuleIdent,
+        member: Name,
+        alias_opt: Option<Name>,
+        modules: &UniqueMap<ModuleIdent, ModuleDefinition>,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        let mod_defs = match self.use_mod_defs(mod_ident) {
+            Some(v) => v,
+            None => return,
+        };
+        let path = format!("{}::{}", mod_to_ide_string(&mod_defs.name), member);
+        let (def_start, ident_type, signature) =
+            if let Some(def) = mod_defs.structs.get(&member.value) {
+                let ident_type = IdentType::RegularType(Self::create_struct_type(
+                    sp(member.loc, mod_defs.name),
+                    StructName(member),
+                    member.loc,
+                    vec![],
+                ));
+                let signature = format!("struct {}", ident_type);
+                (def.name_start, ident_type, signature)
+            } else if let Some(func_def) = mod_defs.functions.get(&member.value) {
+                let signature = format!("{}", func_def.ident_type);
+                (func_def.start, func_def.ident_type.clone(), signature)
+            } else if let Some(def_start) = mod_defs.constants.get(&member.value) {
+                let c = match modules
+                    .get_(&mod_defs.name)
+                    .and_then(|mod_def| mod_def.constants.get_(&member.value))
+                {
+                    Some(v) => v,
+                    None => {
+                        debug_assert!(false);
+                        return;
+                    }
+                };
+                let ident_type = IdentType::RegularType(c.signature.clone());
+                let signature = format!("const {}: {}", path, ident_type);
+                (*def_start, ident_type, signature)
+            } else {
+                return;
+            };
+        let alias = format!(
+            "alias {} = {}\n{}",
+            alias_opt.unwrap_or(member),
+            path,
+            signature
+        );
+        self.add_alias_use_defs(
+            member,
+            alias_opt,
+            mod_defs.fhash,
+            def_start,
+            ident_type,
+            alias,
+            references,
+            use_defs,
+        );
+    }
+
+    /// Add uses of an aliased definition for a name in a `use` declaration and for its alias
+    fn add_alias_use_defs(
+        &self,
+        name: Name,
+        alias_opt: Option<Name>,
+        def_fhash: FileHash,
+        def_start: Position,
+        ident_type: IdentType,
+        alias: String,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        let doc_string = self.extract_doc_string(&def_start, &def_fhash);
+        let ident_type_def = self.ident_type_def_loc(&ident_type);
+        for use_name in std::iter::once(name).chain(alias_opt) {
+            let use_start =
+                match Self::get_start_loc(&use_name.loc, &self.files, &self.file_id_mapping) {
+                    Some(s) => s,
+                    None => {
+                        debug_assert!(false);
+                        continue;
+                    }
+                };
+            use_defs.insert(
+                use_start.line,
+                UseDef::new(
+                    references,
+                    use_name.loc.file_hash(),
+                    use_start,
+                    def_fhash,
+                    def_start,
+                    &use_name.value,
+                    ident_type.clone(),
+                    ident_type_def,
+                    doc_string.clone(),
+                )
+                .with_description(alias.clone()),
+            );
+        }
+    }
+
+    /// Get symbols for spec schemas defined in a module and for the places where they are
+    /// included (spec blocks are not retained in the typed AST)
+    fn spec_symbols(
+        &self,
+        parsed_mod_def: &P::ModuleDefinition,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        let mod_ident = self.current_mod.unwrap().value;
+        for member in &parsed_mod_def.members {
+            let spec_block = match member {
+                P::ModuleMember::Spec(sp!(_, spec_block)) => spec_block,
+                _ => continue,
+            };
+            if let P::SpecBlockTarget_::Schema(name, _) = &spec_block.target.value {
+                self.add_schema_use_def(&mod_ident, name, references, use_defs);
+            }
+            for spec_member in &spec_block.members {
+                if let P::SpecBlockMember_::Include { exp, .. } = &spec_member.value {
+                    self.include_symbols(parsed_mod_def, exp, references, use_defs);
+                }
+            }
+        }
+    }
+
+    /// Get the ranges of lines that can be folded in a module: the module body, function bodies,
+    /// struct declarations and spec blocks (constructs spanning a single line are skipped)
+    fn folding_ranges(&self, parsed_mod_def: &P::ModuleDefinition) -> Vec<FoldingRange> {
+        let mut locs = vec![parsed_mod_def.loc];
+        for member in &parsed_mod_def.members {
+            match member {
+                P::ModuleMember::Function(fun) => {
+                    if let P::FunctionBody_::Defined(_) = fun.body.value {
+                        locs.push(fun.body.loc);
+                    }
+                }
+                P::ModuleMember::Struct(struct_def) => locs.push(struct_def.loc),
+                P::ModuleMember::Spec(spec_block) => locs.push(spec_block.loc),
+                P::ModuleMember::Use(_)
+                | P::ModuleMember::Friend(_)
+                | P::ModuleMember::Constant(_) => {}
+            }
+        }
+
+        locs.into_iter()
+            .filter_map(|loc| {
+                let start = get_loc(
+                    &loc.file_hash(),
+                    loc.start(),
+                    &self.files,
+                    &self.file_id_mapping,
+                )?;
+                let end = get_loc(
+                    &loc.file_hash(),
+                    loc.end(),
+                    &self.files,
+                    &self.file_id_mapping,
+                )?;
+                (start.line < end.line).then_some(FoldingRange {
+                    start_line: start.line,
+                    start_character: None,
+                    end_line: end.line,
+                    end_character: None,
+                    kind: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Get symbols for schemas included by the expression of an `include` spec block member
+    fn include_symbols(
+        &self,
+        parsed_mod_def: &P::ModuleDefinition,
+        sp!(_, exp): &P::Exp,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        match exp {
+            P::Exp_::Name(chain, _) | P::Exp_::Pack(chain, _, _) => {
+                if let Some((mod_ident, name)) = self.schema_access(parsed_mod_def, chain) {
+                    self.add_schema_use_def(&mod_ident, name, references, use_defs);
+                }
+            }
+            // include p ==> S
+            P::Exp_::BinopExp(_, _, rhs) => {
+                self.include_symbols(parsed_mod_def, rhs, references, use_defs)
+            }
+            // include if (p) S1 else S2
+            P::Exp_::IfElse(_, e1, e2_opt) => {
+                self.include_symbols(parsed_mod_def, e1, references, use_defs);
+                if let Some(e2) = e2_opt {
+                    self.include_symbols(parsed_mod_def, e2, references, use_defs);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Get the defining module and the name of a schema referred to by a name access chain
+    fn schema_access<'a>(
+        &self,
+        parsed_mod_def: &P::ModuleDefinition,
+        sp!(loc, chain): &'a P::NameAccessChain,
+    ) -> Option<(ModuleIdent_, &'a Name)> {
+        match chain {
+            P::NameAccessChain_::One(name) => Some((self.current_mod.unwrap().value, name)),
+            P::NameAccessChain_::Two(sp!(_, P::LeadingNameAccess_::Name(mod_name)), name) => {
+                // the module must have been imported via a `use` declaration
+                let mod_ident = parsed_mod_def
+                    .members
+                    .iter()
+                    .find_map(|member| match member {
+                        P::ModuleMember::Use(use_decl) => match &use_decl.use_ {
+                            P::Use::Module(mod_ident, alias_opt) => {
+                                let alias = alias_opt.unwrap_or(mod_ident.value.module);
+                                (alias.value() == mod_name.value).then_some(mod_ident)
+                            }
+                            P::Use::Members(mod_ident, members) => {
+                                members.iter().find_map(|(member, alias_opt)| {
+                                    let alias = alias_opt.unwrap_or(mod_ident.value.module.0);
+                                    (member.value.as_str() == P::ModuleName::SELF_NAME
+                                        && alias.value == mod_name.value)
+                                        .then_some(mod_ident)
+                                })
+                            }
+                        },
+                        _ => None,
+                    })?;
+                Some((self.use_mod_defs(mod_ident)?.name, name))
+            }
+            P::NameAccessChain_::Two(_, _) => None,
+            P::NameAccessChain_::Three(sp!(_, (address, module)), name) => {
+                let mod_ident = sp(
+                    *loc,
+                    P::ModuleIdent_ {
+                        address: *address,
+                        module: P::ModuleName(*module),
+                    },
+                );
+                Some((self.use_mod_defs(&mod_ident)?.name, name))
+            }
+        }
+    }
+
+    /// Add use of a spec schema identifier
+    fn add_schema_use_def(
+        &self,
+        mod_ident: &ModuleIdent_,
+        use_name: &Name,
+        references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
+        use_defs: &mut UseDefMap,
+    ) {
+        let mod_defs = match self.mod_outer_defs.get(mod_ident) {
+            Some(v) => v,
+            None => return,
+        };
+        let def_start = match mod_defs.schemas.get(&use_name.value) {
+            Some(v) => v,
+            None => return,
+        };
+        let use_start = match Self::get_start_loc(&use_name.loc, &self.files, &self.file_id_mapping)
+        {
+            Some(s) => s,
+            None => {
+                debug_assert!(false);
+                return;
+            }
+        };
+        let doc_string = self.extract_doc_string(def_start, &mod_defs.fhash);
+        // schemas do not have a type
+        let ident_type = IdentType::RegularType(sp(use_name.loc, Type_::Unit));
+        let description = format!("schema {}::{}", mod_to_ide_string(mod_ident), use_name);
+        use_defs.insert(
+            use_start.line,
+            UseDef::new(
+                references,
+                use_name.loc.file_hash(),
+                use_start,
+                mod_defs.fhash,
+                *def_start,
+                &use_name.value,
+                ident_type,
+                None,
+                doc_string,
+            )
+            .with_description(description),
+        );
+    }
+
+    /// Get definitions of a module referred to in a `use` declaration
+    fn use_mod_defs(&self, sp!(_, mod_ident): &P::ModuleIdent) -> Option<&ModuleDefs> {
+        self.mod_outer_defs.values().find(|mod_defs| {
+            mod_defs.name.module.value() == mod_ident.module.value()
+                && match (&mod_defs.name.address, &mod_ident.address.value) {
+                    (
+                        Address::Numerical(_, sp!(_, addr)),
+                        P::LeadingNameAccess_::AnonymousAddress(a),
+                    ) => addr == a,
+                    (Address::Numerical(Some(name), _), P::LeadingNameAccess_::Name(n))
+                    | (Address::NamedUnassigned(name), P::LeadingNameAccess_::Name(n)) => {
+                        name.value == n.value
+                    }
+                    _ => false,
+                }
+        })
+    }
+
     /// Get symbols for function a definition
     fn struct_symbols(
         &mut self,
@@ -1084,6 +1786,12 @@ impl Symbolicator {
         // scope for the main function scope (for parameters and
         // function body)
         let mut scope = OrdMap::new();
+        self.block_end = get_loc(
+            &fun.body.loc.file_hash(),
+            fun.body.loc.end(),
+            &self.files,
+            &self.file_id_mapping,
+        );
 
         for (pname, ptype) in &fun.signature.parameters {
             self.add_type_id_use_def(ptype, references, use_defs);
@@ -1125,6 +1833,7 @@ impl Symbolicator {
 
         // clear type params from the scope
         self.type_params.clear();
+        self.block_end = None;
     }
 
     fn get_start_loc(
@@ -1205,7 +1914,7 @@ impl Symbolicator {
 
     /// Get symbols for a sequence representing function body
     fn seq_item_symbols(
-        &self,
+        &mut self,
         scope: &mut OrdMap<Symbol, DefLoc>,
         seq_item: &SequenceItem,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1234,7 +1943,7 @@ impl Symbolicator {
 
     /// Get symbols for a list of lvalues
     fn lvalue_list_symbols(
-        &self,
+        &mut self,
         define: bool,
         lvalues: &LValueList,
         scope: &mut OrdMap<Symbol, DefLoc>,
@@ -1248,7 +1957,7 @@ impl Symbolicator {
 
     /// Get symbols for a single lvalue
     fn lvalue_symbols(
-        &self,
+        &mut self,
         define: bool,
         lval: &LValue,
         scope: &mut OrdMap<Symbol, DefLoc>,
@@ -1293,7 +2002,7 @@ impl Symbolicator {
 
     /// Get symbols for the unpack statement
     fn unpack_symbols(
-        &self,
+        &mut self,
         define: bool,
         ident: &ModuleIdent,
         name: &StructName,
@@ -1335,7 +2044,7 @@ impl Symbolicator {
 
     /// Get symbols for an expression
     fn exp_symbols(
-        &self,
+        &mut self,
         exp: &Exp,
         scope: &mut OrdMap<Symbol, DefLoc>,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1413,9 +2122,17 @@ impl Symbolicator {
             E::Block(sequence) => {
                 // a block is a new var scope
                 let mut new_scope = scope.clone();
+                let outer_block_end = self.block_end;
+                self.block_end = get_loc(
+                    &exp.exp.loc.file_hash(),
+                    exp.exp.loc.end(),
+                    &self.files,
+                    &self.file_id_mapping,
+                );
                 for seq_item in sequence {
                     self.seq_item_symbols(&mut new_scope, seq_item, references, use_defs);
                 }
+                self.block_end = outer_block_end;
             }
             E::Assign(lvalues, opt_types, e) => {
                 self.lvalue_list_symbols(false, lvalues, scope, references, use_defs);
@@ -1526,7 +2243,7 @@ impl Symbolicator {
     }
 
     fn mod_call_symbols(
-        &self,
+        &mut self,
         mod_call: &ModuleCall,
         scope: &mut OrdMap<Symbol, DefLoc>,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1561,7 +2278,7 @@ impl Symbolicator {
 
     /// Get symbols for the pack expression
     fn pack_symbols(
-        &self,
+        &mut self,
         ident: &ModuleIdent,
         name: &StructName,
         tparams: &Vec<Type>,
@@ -1906,7 +2623,7 @@ impl Symbolicator {
 
     /// Add a "generic" definition
     fn add_def(
-        &self,
+        &mut self,
         pos: &Loc,
         name: &Symbol,
         scope: &mut OrdMap<Symbol, DefLoc>,
@@ -1921,6 +2638,9 @@ impl Symbolicator {
                     start: name_start,
                 };
                 scope.insert(*name, def_loc);
+                if let Some(block_end) = self.block_end {
+                    self.local_scope_ends.insert(def_loc, block_end);
+                }
                 // in other languages only one definition is allowed per scope but in move an (and
                 // in rust) a variable can be re-defined in the same scope replacing the previous
                 // definition
@@ -2046,34 +2766,211 @@ pub fn on_go_to_def_request(context: &Context, request: &Request, symbols: &Symb
         .uri
         .to_file_path()
         .unwrap();
-    let loc = parameters.text_document_position_params.position;
-    let line = loc.line;
-    let col = loc.character;
-
-    on_use_request(
-        context,
-        symbols,
-        &fpath,
-        line,
-        col,
-        request.id.clone(),
-        |u| {
-            // TODO: Do we need beginning and end of the definition? Does not seem to make a
-            // difference from the IDE perspective as the cursor goes to the beginning anyway (at
-            // least in VSCode).
-            let range = Range {
-                start: u.def_loc.start,
-                end: u.def_loc.start,
-            };
-            let path = symbols.file_name_mapping.get(&u.def_loc.fhash).unwrap();
-            let loc = Location {
-                uri: Url::from_file_path(path.as_str()).unwrap(),
-                range,
-            };
-            Some(serde_json::to_value(loc).unwrap())
-        },
-    );
-}
+    let pos = parameters.text_document_position_params.position;
+    let location = symbols
+        .def_locations(&fpath, &[(pos.line, pos.character)])
+        .pop()
+        .flatten();
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), location);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send go-to-def response: {:?}", err);
+    }
+}
+
+/// Number of lines shown before and after a definition's line when peeking at it, unless the
+/// client asks for a different number
+pub const DEFAULT_PEEK_CONTEXT_LINES: u32 = 3;
+
+/// Custom request for a definition's location along with the source lines around it, so that the
+/// client can show the definition inline (e.g., in a peek view) instead of navigating to it
+pub enum PeekDefinitionRequest {}
+
+impl lsp_types::request::Request for PeekDefinitionRequest {
+    type Params = PeekDefinitionParams;
+    type Result = Option<PeekDefinition>;
+    const METHOD: &'static str = "move-analyzer/peekDefinition";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeekDefinitionParams {
+    #[serde(flatten)]
+    pub text_document_position_params: TextDocumentPositionParams,
+    /// Number of lines to include before and after the definition's line (defaults to
+    /// `DEFAULT_PEEK_CONTEXT_LINES`)
+    pub context_lines: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeekDefinition {
+    /// Location of the definition
+    pub location: Location,
+    /// Line number of the first line in `lines`
+    pub first_line: u32,
+    /// Source lines around the definition
+    pub lines: Vec<String>,
+}
+
+/// Handles peek-definition request of the language server
+pub fn on_peek_definition_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<PeekDefinitionParams>(request.params.clone())
+        .expect("could not deserialize peek-definition request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+    let line = loc.line;
+    let col = loc.character;
+    let context_lines = parameters
+        .context_lines
+        .unwrap_or(DEFAULT_PEEK_CONTEXT_LINES);
+
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        line,
+        col,
+        request.id.clone(),
+        |u| {
+            let peek = peek_definition(symbols, &u.def_loc, context_lines);
+            Some(serde_json::to_value(peek).unwrap())
+        },
+    );
+}
+
+/// Location of the definition at `def_loc` and the source lines around it (at most
+/// `context_lines` on each side), or `None` if its file cannot be read
+fn peek_definition(
+    symbols: &Symbols,
+    def_loc: &DefLoc,
+    context_lines: u32,
+) -> Option<PeekDefinition> {
+    let path = symbols.file_name_mapping.get(&def_loc.fhash).unwrap();
+    let contents = std::fs::read_to_string(path.as_str()).ok()?;
+
+    let def_line = def_loc.start.line;
+    let first_line = def_line.saturating_sub(context_lines);
+    let lines = contents
+        .lines()
+        .skip(first_line as usize)
+        .take(
+            (def_line - first_line)
+                .saturating_add(context_lines)
+                .saturating_add(1) as usize,
+        )
+        .map(String::from)
+        .collect();
+
+    Some(PeekDefinition {
+        location: def_ide_location(symbols, def_loc),
+        first_line,
+        lines,
+    })
+}
+
+/// Location of the definition at `def_loc`, as reported to the IDE
+fn def_ide_location(symbols: &Symbols, def_loc: &DefLoc) -> Location {
+    // TODO: Do we need beginning and end of the definition? Does not seem to make a difference
+    // from the IDE perspective as the cursor goes to the beginning anyway (at least in VSCode).
+    let range = Range {
+        start: def_loc.start,
+        end: def_loc.start,
+    };
+    let path = symbols.file_name_mapping.get(&def_loc.fhash).unwrap();
+    Location {
+        uri: Url::from_file_path(path.as_str()).unwrap(),
+        range,
+    }
+}
+
+/// Handles go-to-implementation request of the language server
+pub fn on_go_to_impl_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<GotoImplementationParams>(request.params.clone())
+        .expect("could not deserialize go-to-implementation request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let pos = parameters.text_document_position_params.position;
+
+    let location = match implementation(symbols, &fpath, &pos) {
+        Some((_, Some(location))) => Some(location),
+        Some((name, None)) => {
+            let params = ShowMessageParams {
+                typ: MessageType::Info,
+                message: format!("`{}` is native (no implementation)", name),
+            };
+            let notification = Notification::new(ShowMessage::METHOD.to_string(), params);
+            if let Err(err) = context
+                .connection
+                .sender
+                .send(lsp_server::Message::Notification(notification))
+            {
+                eprintln!("could not send native function notification: {:?}", err);
+            }
+            None
+        }
+        None => None,
+    };
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), location);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send go-to-implementation response: {:?}", err);
+    }
+}
+
+/// Name of the function used or declared at `pos` in the file at `fpath`, along with the location
+/// of its body (`None` if the function is native), or `None` if there is no function at `pos`
+fn implementation(
+    symbols: &Symbols,
+    fpath: &Path,
+    pos: &Position,
+) -> Option<(Symbol, Option<Location>)> {
+    let use_defs = symbols.file_use_defs.get(fpath)?;
+    let u = use_defs
+        .0
+        .get(&pos.line)?
+        .iter()
+        .find(|u| pos.character >= u.col_start && pos.character <= u.col_end)?;
+    let IdentType::FunctionType(mod_ident, name, ..) = &u.use_type else {
+        return None;
+    };
+
+    let (mod_defs, fun_def) = symbols
+        .file_mods
+        .values()
+        .flatten()
+        .filter(|mod_defs| mod_defs.name == *mod_ident)
+        .find_map(|mod_defs| Some((mod_defs, mod_defs.functions.get(name)?)))?;
+    let location = fun_def.body_start.map(|start| {
+        def_ide_location(
+            symbols,
+            &DefLoc {
+                fhash: mod_defs.fhash,
+                start,
+            },
+        )
+    });
+    Some((*name, location))
+}
 
 /// Handles go-to-type-def request of the language server
 pub fn on_go_to_type_def_request(context: &Context, request: &Request, symbols: &Symbols) {
@@ -2099,15 +2996,7 @@ pub fn on_go_to_type_def_request(context: &Context, request: &Request, symbols:
         request.id.clone(),
         |u| match u.type_def_loc {
             Some(def_loc) => {
-                let range = Range {
-                    start: def_loc.start,
-                    end: def_loc.start,
-                };
-                let path = symbols.file_name_mapping.get(&u.def_loc.fhash).unwrap();
-                let loc = Location {
-                    uri: Url::from_file_path(path.as_str()).unwrap(),
-                    range,
-                };
+                let loc = def_ide_location(symbols, &def_loc);
                 Some(serde_json::to_value(loc).unwrap())
             }
             None => Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap()),
@@ -2131,6 +3020,8 @@ pub fn on_references_request(context: &Context, request: &Request, symbols: &Sym
     let col = loc.character;
     let include_decl = parameters.context.include_declaration;
 
+    let max_results = context.config.max_results;
+
     on_use_request(
         context,
         symbols,
@@ -2138,43 +3029,24 @@ pub fn on_references_request(context: &Context, request: &Request, symbols: &Sym
         line,
         col,
         request.id.clone(),
-        |u| match symbols.references.get(&u.def_loc) {
-            Some(s) => {
-                let mut locs = vec![];
-                for ref_loc in s {
-                    if include_decl
-                        || !(u.def_loc.start == ref_loc.start && u.def_loc.fhash == ref_loc.fhash)
-                    {
-                        let end_pos = Position {
-                            line: ref_loc.start.line,
-                            character: ref_loc.col_end,
-                        };
-                        let range = Range {
-                            start: ref_loc.start,
-                            end: end_pos,
-                        };
-                        let path = symbols.file_name_mapping.get(&ref_loc.fhash).unwrap();
-                        locs.push(Location {
-                            uri: Url::from_file_path(path.as_str()).unwrap(),
-                            range,
-                        });
-                    }
-                }
-                if locs.is_empty() {
-                    Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap())
-                } else {
-                    Some(serde_json::to_value(locs).unwrap())
-                }
+        |u| {
+            let (locs, truncated) = references(symbols, &u.def_loc, include_decl, max_results);
+            if truncated {
+                notify_truncated_results(context, "references", max_results);
+            }
+            if locs.is_empty() {
+                Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap())
+            } else {
+                Some(serde_json::to_value(locs).unwrap())
             }
-            None => Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap()),
         },
     );
 }
 
-/// Handles hover request of the language server
-pub fn on_hover_request(context: &Context, request: &Request, symbols: &Symbols) {
-    let parameters = serde_json::from_value::<HoverParams>(request.params.clone())
-        .expect("could not deserialize hover request");
+/// Handles document highlight request of the language server
+pub fn on_document_highlight_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<DocumentHighlightParams>(request.params.clone())
+        .expect("could not deserialize document highlight request");
 
     let fpath = parameters
         .text_document_position_params
@@ -2185,6 +3057,7 @@ pub fn on_hover_request(context: &Context, request: &Request, symbols: &Symbols)
     let loc = parameters.text_document_position_params.position;
     let line = loc.line;
     let col = loc.character;
+    let max_results = context.config.max_results;
 
     on_use_request(
         context,
@@ -2194,302 +3067,1562 @@ pub fn on_hover_request(context: &Context, request: &Request, symbols: &Symbols)
         col,
         request.id.clone(),
         |u| {
-            let lang_string = LanguageString {
-                language: "".to_string(),
-                value: if !u.doc_string.is_empty() {
-                    format!("{}\n\n{}", u.use_type, u.doc_string)
-                } else {
-                    format!("{}", u.use_type)
-                },
-            };
-            let contents = HoverContents::Scalar(MarkedString::LanguageString(lang_string));
-            let range = None;
-            Some(serde_json::to_value(Hover { contents, range }).unwrap())
+            let mut highlights: Vec<_> = file_references(symbols, &u.def_loc, &fpath)
+                .into_iter()
+                .map(|range| DocumentHighlight { range, kind: None })
+                .collect();
+            if truncate_results(&mut highlights, max_results) {
+                notify_truncated_results(context, "highlights", max_results);
+            }
+            Some(serde_json::to_value(highlights).unwrap())
         },
     );
 }
 
-/// Helper function to handle language server queries related to identifier uses
-pub fn on_use_request(
-    context: &Context,
-    symbols: &Symbols,
-    use_fpath: &PathBuf,
-    use_line: u32,
-    use_col: u32,
-    id: RequestId,
-    use_def_action: impl Fn(&UseDef) -> Option<serde_json::Value>,
-) {
-    let mut result = None;
+/// Handles linked editing range request of the language server
+pub fn on_linked_editing_range_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<LinkedEditingRangeParams>(request.params.clone())
+        .expect("could not deserialize linked editing range request");
 
-    let mut use_def_found = false;
-    if let Some(mod_symbols) = symbols.file_use_defs.get(use_fpath) {
-        if let Some(uses) = mod_symbols.get(use_line) {
-            for u in uses {
-                if use_col >= u.col_start && use_col <= u.col_end {
-                    result = use_def_action(&u);
-                    use_def_found = true;
-                }
-            }
-        }
-    }
-    if !use_def_found {
-        result = Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap());
-    }
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+    let line = loc.line;
+    let col = loc.character;
 
-    eprintln!("about to send use response");
-    // unwrap will succeed based on the logic above which the compiler is unable to figure out
-    // without using Option
-    let response = lsp_server::Response::new_ok(id, result.unwrap());
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        line,
+        col,
+        request.id.clone(),
+        |u| match linked_editing_ranges(symbols, &u.def_loc, &fpath) {
+            Some(ranges) => Some(serde_json::to_value(ranges).unwrap()),
+            None => Some(serde_json::to_value(Option::<LinkedEditingRanges>::None).unwrap()),
+        },
+    );
+}
+
+/// Handles rename request of the language server
+pub fn on_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<RenameParams>(request.params.clone())
+        .expect("could not deserialize rename request");
+
+    let fpath = parameters
+        .text_document_position
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position.position;
+    let line = loc.line;
+    let col = loc.character;
+
+    let use_def = symbols
+        .file_use_defs
+        .get(&fpath)
+        .and_then(|mod_symbols| mod_symbols.get(line))
+        .and_then(|uses| {
+            uses.into_iter()
+                .find(|u| col >= u.col_start && col <= u.col_end)
+        });
+
+    let response = match use_def {
+        None => lsp_server::Response::new_ok(request.id.clone(), Option::<WorkspaceEdit>::None),
+        Some(u) => match rename_edits(symbols, &context.files, &u.def_loc, &parameters.new_name) {
+            Ok(edit) => lsp_server::Response::new_ok(request.id.clone(), edit),
+            Err(msg) => lsp_server::Response::new_err(
+                request.id.clone(),
+                ErrorCode::InvalidRequest as i32,
+                msg,
+            ),
+        },
+    };
     if let Err(err) = context
         .connection
         .sender
         .send(lsp_server::Message::Response(response))
     {
-        eprintln!("could not send use response: {:?}", err);
+        eprintln!("could not send rename response: {:?}", err);
     }
 }
 
-/// Handles document symbol request of the language server
-#[allow(deprecated)]
-pub fn on_document_symbol_request(context: &Context, request: &Request, symbols: &Symbols) {
-    let parameters = serde_json::from_value::<DocumentSymbolParams>(request.params.clone())
-        .expect("could not deserialize document symbol request");
+/// Computes the edits renaming the definition at `def_loc` and all its references to `new_name`,
+/// or the reason why the definition cannot be renamed. The scope of the definition is approximated
+/// by the lines between its first and last reference in each file (and by its module, for module
+/// members), and renaming it to the name of another definition referenced there is rejected. Files
+/// are read from `files` if they are open in the editor.
+fn rename_edits(
+    symbols: &Symbols,
+    files: &VirtualFileSystem,
+    def_loc: &DefLoc,
+    new_name: &str,
+) -> std::result::Result<WorkspaceEdit, String> {
+    let is_identifier = new_name
+        .chars()
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && new_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier || KEYWORDS.contains(&new_name) {
+        return Err(format!("'{}' is not a valid identifier", new_name));
+    }
 
-    let fpath = parameters.text_document.uri.to_file_path().unwrap();
-    eprintln!("on_document_symbol_request: {:?}", fpath);
+    if symbols.dependency_files.contains(&def_loc.fhash) {
+        return Err("Cannot rename a definition in a dependency package".to_string());
+    }
 
-    let empty_mods: BTreeSet<ModuleDefs> = BTreeSet::new();
-    let mods = symbols.file_mods.get(&fpath).unwrap_or(&empty_mods);
+    if module_member_names(symbols, def_loc).contains(&Symbol::from(new_name)) {
+        return Err(format!(
+            "Cannot rename to '{}', the module already has a member with this name",
+            new_name
+        ));
+    }
 
-    let mut defs: Vec<DocumentSymbol> = vec![];
-    for mod_def in mods {
-        let name = mod_def.name.module.clone().to_string();
-        let detail = Some(mod_def.name.clone().to_string());
-        let kind = SymbolKind::Module;
-        let range = Range {
-            start: mod_def.start,
-            end: mod_def.start,
+    let mut file_lines: BTreeMap<FileHash, (u32, u32)> = BTreeMap::new();
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for ref_loc in symbols.references.get(def_loc).into_iter().flatten() {
+        let lines = file_lines
+            .entry(ref_loc.fhash)
+            .or_insert((ref_loc.start.line, ref_loc.start.line));
+        lines.0 = cmp::min(lines.0, ref_loc.start.line);
+        lines.1 = cmp::max(lines.1, ref_loc.start.line);
+
+        let path = symbols.file_name_mapping.get(&ref_loc.fhash).unwrap();
+        changes
+            .entry(Url::from_file_path(path.as_str()).unwrap())
+            .or_default()
+            .push(TextEdit {
+                range: Range {
+                    start: ref_loc.start,
+                    end: Position {
+                        line: ref_loc.start.line,
+                        character: ref_loc.col_end,
+                    },
+                },
+                new_text: new_name.to_string(),
+            });
+    }
+
+    for (fhash, (first_line, last_line)) in file_lines {
+        let path = symbols.file_name_mapping.get(&fhash).unwrap();
+        let fpath =
+            dunce::canonicalize(path.as_str()).unwrap_or_else(|_| PathBuf::from(path.as_str()));
+        let contents = file_contents(files, &fpath).map_err(|err| err.to_string())?;
+        let lines: Vec<_> = contents.lines().collect();
+        let Some(mod_symbols) = symbols.file_use_defs.get(&fpath) else {
+            continue;
         };
 
-        let mut children = vec![];
+        for line in first_line..=last_line {
+            for u in mod_symbols.get(line).into_iter().flatten() {
+                if u.def_loc != *def_loc && use_name(&lines, line, &u) == new_name {
+                    return Err(format!(
+                        "Cannot rename to '{}', which already refers to another definition in scope",
+                        new_name
+                    ));
+                }
+            }
+        }
+    }
 
-        // handle constants
-        let cloned_const_def = mod_def.constants.clone();
-        for (sym, const_def_pos) in cloned_const_def {
-            let const_range = Range {
-                start: const_def_pos,
-                end: const_def_pos,
-            };
+    Ok(WorkspaceEdit::new(changes))
+}
 
-            children.push(DocumentSymbol {
-                name: sym.clone().to_string(),
-                detail: None,
-                kind: SymbolKind::Constant,
-                range: const_range,
-                selection_range: const_range,
-                children: None,
-                tags: Some(vec![]),
-                deprecated: Some(false),
-            });
+/// Contents of the file at `path`: its buffer in `files` if it is open in the editor (possibly with
+/// unsaved changes), and its contents on disk otherwise
+fn file_contents(files: &VirtualFileSystem, path: &PathBuf) -> std::io::Result<String> {
+    match files.get(path) {
+        Some(buffer) => Ok(buffer.to_string()),
+        None => std::fs::read_to_string(path),
+    }
+}
+
+/// Name of the identifier of the use `u` on the given line of a file's `lines`
+fn use_name(lines: &[&str], line: u32, u: &UseDef) -> String {
+    lines
+        .get(line as usize)
+        .unwrap_or(&"")
+        .chars()
+        .skip(u.col_start as usize)
+        .take((u.col_end - u.col_start) as usize)
+        .collect()
+}
+
+/// Names of the members of the module declaring the definition at `def_loc`, if it is itself a
+/// module member (otherwise empty)
+fn module_member_names(symbols: &Symbols, def_loc: &DefLoc) -> BTreeSet<Symbol> {
+    for mod_defs in symbols.file_mods.values().flatten() {
+        if mod_defs.fhash != def_loc.fhash {
+            continue;
         }
+        let is_member = mod_defs
+            .functions
+            .values()
+            .any(|f| f.start == def_loc.start)
+            || mod_defs
+                .structs
+                .values()
+                .any(|s| s.name_start == def_loc.start)
+            || mod_defs.constants.values().any(|p| *p == def_loc.start)
+            || mod_defs.schemas.values().any(|p| *p == def_loc.start);
+        if is_member {
+            return mod_defs
+                .functions
+                .keys()
+                .chain(mod_defs.structs.keys())
+                .chain(mod_defs.constants.keys())
+                .chain(mod_defs.schemas.keys())
+                .copied()
+                .collect();
+        }
+    }
+    BTreeSet::new()
+}
 
-        // handle structs
-        let cloned_struct_def = mod_def.structs.clone();
-        for (sym, struct_def) in cloned_struct_def {
-            let struct_range = Range {
-                start: struct_def.name_start,
-                end: struct_def.name_start,
-            };
+/// Computes ranges in the file at `fpath` that can be edited together with the definition at
+/// `def_loc` (the definition itself and all its uses in this file). Editing only some of the
+/// references would break the code if the definition is not in this file, in which case there are
+/// no linked ranges.
+fn linked_editing_ranges(
+    symbols: &Symbols,
+    def_loc: &DefLoc,
+    fpath: &Path,
+) -> Option<LinkedEditingRanges> {
+    if !file_hashes(symbols, fpath).contains(&def_loc.fhash) {
+        return None;
+    }
+    Some(LinkedEditingRanges {
+        ranges: file_references(symbols, def_loc, fpath),
+        word_pattern: None,
+    })
+}
 
-            let mut fields: Vec<DocumentSymbol> = vec![];
-            handle_struct_fields(struct_def, &mut fields);
+/// Computes ranges of all references to a given definition (including the definition itself)
+/// located in the file at `fpath`
+fn file_references(symbols: &Symbols, def_loc: &DefLoc, fpath: &Path) -> Vec<Range> {
+    let fhashes = file_hashes(symbols, fpath);
+    symbols
+        .references
+        .get(def_loc)
+        .into_iter()
+        .flatten()
+        .filter(|ref_loc| fhashes.contains(&ref_loc.fhash))
+        .map(|ref_loc| Range {
+            start: ref_loc.start,
+            end: Position {
+                line: ref_loc.start.line,
+                character: ref_loc.col_end,
+            },
+        })
+        .collect()
+}
 
-            children.push(DocumentSymbol {
-                name: sym.clone().to_string(),
-                detail: None,
-                kind: SymbolKind::Struct,
-                range: struct_range,
-                selection_range: struct_range,
-                children: Some(fields),
-                tags: Some(vec![]),
-                deprecated: Some(false),
+/// Finds hashes of the file at `fpath`
+fn file_hashes(symbols: &Symbols, fpath: &Path) -> BTreeSet<FileHash> {
+    symbols
+        .file_name_mapping
+        .iter()
+        .filter(|(_, path)| {
+            dunce::canonicalize(path.as_str()).unwrap_or_else(|_| PathBuf::from(path.as_str()))
+                == fpath
+        })
+        .map(|(fhash, _)| *fhash)
+        .collect()
+}
+
+/// Computes locations of all references to a given definition (including the definition itself if
+/// `include_decl` is set), up to `max_results` of them (0 meaning no limit). Also returns whether
+/// any references had to be dropped to stay within this limit.
+fn references(
+    symbols: &Symbols,
+    def_loc: &DefLoc,
+    include_decl: bool,
+    max_results: usize,
+) -> (Vec<Location>, bool) {
+    let mut locs = vec![];
+    for ref_loc in symbols.references.get(def_loc).into_iter().flatten() {
+        if include_decl || !(def_loc.start == ref_loc.start && def_loc.fhash == ref_loc.fhash) {
+            let end_pos = Position {
+                line: ref_loc.start.line,
+                character: ref_loc.col_end,
+            };
+            let range = Range {
+                start: ref_loc.start,
+                end: end_pos,
+            };
+            let path = symbols.file_name_mapping.get(&ref_loc.fhash).unwrap();
+            locs.push(Location {
+                uri: Url::from_file_path(path.as_str()).unwrap(),
+                range,
             });
         }
+    }
+    let truncated = truncate_results(&mut locs, max_results);
+    (locs, truncated)
+}
 
-        // handle functions
-        let cloned_func_def = mod_def.functions.clone();
-        for (sym, func_def) in cloned_func_def {
-            let func_range = Range {
-                start: func_def.start,
-                end: func_def.start,
+/// Truncates query results to at most `max_results` elements (0 meaning no limit), returning true
+/// if any results were dropped
+fn truncate_results<T>(results: &mut Vec<T>, max_results: usize) -> bool {
+    if max_results == 0 || results.len() <= max_results {
+        return false;
+    }
+    results.truncate(max_results);
+    true
+}
+
+/// Lets the user know that only some of the results of a query are being shown
+fn notify_truncated_results(context: &Context, results_kind: &str, max_results: usize) {
+    let params = ShowMessageParams {
+        typ: MessageType::Warning,
+        message: format!(
+            "Too many {} found, only the first {} are shown",
+            results_kind, max_results
+        ),
+    };
+    let notification = Notification::new(ShowMessage::METHOD.to_string(), params);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Notification(notification))
+    {
+        eprintln!("could not send truncated results notification: {:?}", err);
+    }
+}
+
+/// Handles signature help request of the language server
+pub fn on_signature_help_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SignatureHelpParams>(request.params.clone())
+        .expect("could not deserialize signature help request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let pos = parameters.text_document_position_params.position;
+
+    let help = context
+        .files
+        .get(&fpath)
+        .and_then(|buffer| signature_help(symbols, &fpath, buffer, &pos));
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), help);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send signature help response: {:?}", err);
+    }
+}
+
+/// Computes the signature of the function called by the call whose argument list encloses `pos`
+/// in `buffer` (the contents of the file at `fpath`), with the argument at `pos` as the active
+/// parameter, or `None` if `pos` is not within the arguments of a call to a known function.
+fn signature_help(
+    symbols: &Symbols,
+    fpath: &Path,
+    buffer: &str,
+    pos: &Position,
+) -> Option<SignatureHelp> {
+    let (callee_pos, commas) = enclosing_call(buffer, pos)?;
+
+    let uses = symbols.file_use_defs.get(fpath)?.get(callee_pos.line)?;
+    let callee = uses
+        .into_iter()
+        .find(|u| callee_pos.character >= u.col_start && callee_pos.character < u.col_end)?;
+    let IdentType::FunctionType(_, _, _, arg_names, arg_types, _, _) = &callee.use_type else {
+        return None;
+    };
+
+    let parameters: Vec<_> = arg_names
+        .iter()
+        .zip(arg_types)
+        .map(|(name, ty)| ParameterInformation {
+            label: ParameterLabel::Simple(format!("{}: {}", name, type_to_ide_string(ty))),
+            documentation: None,
+        })
+        .collect();
+
+    // a trailing comma after the last argument keeps the last parameter active
+    let active_parameter = if parameters.is_empty() {
+        None
+    } else {
+        Some(cmp::min(commas, parameters.len() as u32 - 1))
+    };
+
+    let documentation = (!callee.doc_string.is_empty())
+        .then(|| lsp_types::Documentation::String(callee.doc_string.clone()));
+
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label: callee.use_type.to_string(),
+            documentation,
+            parameters: Some(parameters),
+            active_parameter,
+        }],
+        active_signature: Some(0),
+        active_parameter,
+    })
+}
+
+/// Finds the innermost call whose argument list encloses `pos` in `buffer`, returning the position
+/// of the last character of the called function's name, and the number of arguments preceding
+/// `pos` in the call (i.e., the number of commas separating them).
+fn enclosing_call(buffer: &str, pos: &Position) -> Option<(Position, u32)> {
+    // characters preceding `pos`, with their positions, from the closest one
+    let mut preceding = buffer
+        .lines()
+        .take(pos.line as usize + 1)
+        .enumerate()
+        .flat_map(|(line, text)| {
+            let len = if line == pos.line as usize {
+                pos.character as usize
+            } else {
+                usize::MAX
             };
+            text.chars()
+                .take(len)
+                .enumerate()
+                .map(move |(col, c)| (Position::new(line as u32, col as u32), c))
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .peekable();
+
+    let mut depth = 0;
+    let mut commas = 0;
+    loop {
+        let (_, c) = preceding.next()?;
+        match c {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '(' => break,
+            // not within the arguments of a call (e.g., within a block or a struct literal)
+            '[' | '{' | ';' => return None,
+            ',' if depth == 0 => commas += 1,
+            _ => (),
+        }
+    }
 
-            let mut detail = None;
-            if !func_def.attrs.is_empty() {
-                detail = Some(format!("{:?}", func_def.attrs));
+    // skip whitespace and type arguments between the function's name and its arguments
+    while preceding.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    if preceding.next_if(|(_, c)| *c == '>').is_some() {
+        let mut depth = 1;
+        while depth > 0 {
+            match preceding.next()?.1 {
+                '>' => depth += 1,
+                '<' => depth -= 1,
+                _ => (),
             }
+        }
+        while preceding.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
 
-            children.push(DocumentSymbol {
-                name: sym.clone().to_string(),
-                detail,
-                kind: SymbolKind::Function,
-                range: func_range,
-                selection_range: func_range,
-                children: None,
-                tags: Some(vec![]),
-                deprecated: Some(false),
+    let (callee_pos, c) = preceding.next()?;
+    (c.is_ascii_alphanumeric() || c == '_').then_some((callee_pos, commas))
+}
+
+/// Types of the semantic tokens reported by the language server, indexed by the token types in
+/// responses to semantic tokens requests
+const SEMANTIC_TOKEN_TYPES: [SemanticTokenType; 5] = [
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+];
+
+/// Modifiers of the semantic tokens reported by the language server (constants are read-only
+/// variables)
+const SEMANTIC_TOKEN_MODIFIERS: [SemanticTokenModifier; 1] = [SemanticTokenModifier::READONLY];
+
+/// Legend of the semantic tokens reported by the language server
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+        token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Handles semantic tokens request of the language server
+pub fn on_semantic_tokens_full_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SemanticTokensParams>(request.params.clone())
+        .expect("could not deserialize semantic tokens request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let buffer = context.files.get(&fpath).unwrap_or("");
+
+    let tokens = SemanticTokens {
+        result_id: None,
+        data: semantic_tokens(symbols, &fpath, buffer),
+    };
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), tokens);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send semantic tokens response: {:?}", err);
+    }
+}
+
+/// Computes the (delta-encoded) semantic tokens of the identifiers in `buffer` (the contents of the
+/// file at `fpath`)
+fn semantic_tokens(symbols: &Symbols, fpath: &Path, buffer: &str) -> Vec<SemanticToken> {
+    let Some(mod_symbols) = symbols.file_use_defs.get(fpath) else {
+        return vec![];
+    };
+    let lines: Vec<&str> = buffer.lines().collect();
+
+    let mut tokens = vec![];
+    let mut prev = Position::new(0, 0);
+    for (line, uses) in &mod_symbols.0 {
+        for u in uses {
+            let (token_type, token_modifiers_bitset) =
+                semantic_token_type(symbols, &lines, *line, u);
+            let delta_start = if *line == prev.line {
+                u.col_start - prev.character
+            } else {
+                u.col_start
+            };
+            tokens.push(SemanticToken {
+                delta_line: line - prev.line,
+                delta_start,
+                length: u.col_end - u.col_start,
+                token_type,
+                token_modifiers_bitset,
             });
+            prev = Position::new(*line, u.col_start);
         }
+    }
+    tokens
+}
 
-        defs.push(DocumentSymbol {
-            name,
-            detail,
-            kind,
-            range,
-            selection_range: range,
-            children: Some(children),
-            tags: Some(vec![]),
-            deprecated: Some(false),
-        });
+/// Classifies the identifier of `u` (on the given line of the file split into `lines`), returning
+/// its token type (an index into `SEMANTIC_TOKEN_TYPES`) and modifiers, based on what it refers to
+fn semantic_token_type(symbols: &Symbols, lines: &[&str], line: u32, u: &UseDef) -> (u32, u32) {
+    const FUNCTION: u32 = 0;
+    const STRUCT: u32 = 1;
+    const TYPE_PARAMETER: u32 = 2;
+    const VARIABLE: u32 = 3;
+    const PROPERTY: u32 = 4;
+    const READONLY: u32 = 1 << 0;
+
+    let ty = match &u.use_type {
+        IdentType::FunctionType(..) => return (FUNCTION, 0),
+        IdentType::RegularType(ty) => ty,
+    };
+
+    for mod_defs in symbols.file_mods.values().flatten() {
+        if mod_defs.fhash != u.def_loc.fhash {
+            continue;
+        }
+        if mod_defs
+            .functions
+            .values()
+            .any(|f| f.start == u.def_loc.start)
+        {
+            return (FUNCTION, 0);
+        }
+        for s in mod_defs.structs.values() {
+            if s.name_start == u.def_loc.start {
+                return (STRUCT, 0);
+            }
+            if s.field_defs.iter().any(|f| f.start == u.def_loc.start) {
+                return (PROPERTY, 0);
+            }
+        }
+        if mod_defs.constants.values().any(|p| *p == u.def_loc.start) {
+            return (VARIABLE, READONLY);
+        }
+        if mod_defs.schemas.values().any(|p| *p == u.def_loc.start) {
+            return (STRUCT, 0);
+        }
     }
 
-    // unwrap will succeed based on the logic above which the compiler is unable to figure out
-    let response = lsp_server::Response::new_ok(request.id.clone(), defs);
+    // a type parameter (rather than a variable of a type parameter's type) is named after it
+    match &ty.value {
+        Type_::Param(tp) if use_name(lines, line, u) == tp.user_specified_name.value.as_str() => {
+            (TYPE_PARAMETER, 0)
+        }
+        _ => (VARIABLE, 0),
+    }
+}
+
+/// Inlay hint request, which the version of LSP implemented by `lsp_types` does not support yet
+pub enum InlayHintRequest {}
+
+impl lsp_types::request::Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Option<Vec<InlayHint>>;
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+/// Kind of inlay hints annotating the type of a binding
+pub const INLAY_HINT_KIND_TYPE: u32 = 1;
+/// Kind of inlay hints annotating the parameter name of an argument
+pub const INLAY_HINT_KIND_PARAMETER: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    /// Range of the document for which hints should be computed
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    /// Position of the hint (it is displayed before the character at this position)
+    pub position: Position,
+    pub label: String,
+    /// Either `INLAY_HINT_KIND_TYPE` or `INLAY_HINT_KIND_PARAMETER`
+    pub kind: u32,
+    /// Whether to add padding after the hint
+    pub padding_right: bool,
+}
+
+/// Handles inlay hint request of the language server
+pub fn on_inlay_hint_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<InlayHintParams>(request.params.clone())
+        .expect("could not deserialize inlay hint request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let hints = context.files.get(&fpath).map(|buffer| {
+        inlay_hints(
+            symbols,
+            &fpath,
+            buffer,
+            &parameters.range,
+            context.config.inlay_hints_parameter_names,
+        )
+    });
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), hints);
     if let Err(err) = context
         .connection
         .sender
         .send(lsp_server::Message::Response(response))
     {
-        eprintln!("could not send use response: {:?}", err);
+        eprintln!("could not send inlay hint response: {:?}", err);
     }
 }
 
-/// Helper function to handle struct fields
-#[allow(deprecated)]
-fn handle_struct_fields(struct_def: StructDef, fields: &mut Vec<DocumentSymbol>) {
-    let clonded_fileds = struct_def.field_defs;
-
-    for field_def in clonded_fileds {
-        let field_range = Range {
-            start: field_def.start,
-            end: field_def.start,
-        };
+/// Computes the inlay hints within `range` of `buffer` (the contents of the file at `fpath`): the
+/// inferred types of `let` bindings declared without a type annotation and, if `parameter_names`
+/// is set, the names of the parameters that call arguments are passed to
+fn inlay_hints(
+    symbols: &Symbols,
+    fpath: &Path,
+    buffer: &str,
+    range: &Range,
+    parameter_names: bool,
+) -> Vec<InlayHint> {
+    let (Some(mods), Some(mod_symbols)) = (
+        symbols.file_mods.get(fpath),
+        symbols.file_use_defs.get(fpath),
+    ) else {
+        return vec![];
+    };
+    let Some(fhash) = mods.iter().next().map(|mod_defs| mod_defs.fhash) else {
+        return vec![];
+    };
+    if range.start.line > range.end.line {
+        return vec![];
+    }
+    let lines: Vec<&str> = buffer.lines().collect();
+
+    let mut hints = vec![];
+    for (line, uses) in mod_symbols.0.range(range.start.line..=range.end.line) {
+        for u in uses {
+            let start = Position::new(*line, u.col_start);
+            let is_decl = u.def_loc.fhash == fhash && u.def_loc.start == start;
+            match &u.use_type {
+                IdentType::RegularType(ty) if is_decl && unannotated_let(&lines, start) => hints
+                    .push(InlayHint {
+                        position: Position::new(*line, u.col_end),
+                        label: format!(": {}", type_to_ide_string(ty)),
+                        kind: INLAY_HINT_KIND_TYPE,
+                        padding_right: false,
+                    }),
+                IdentType::FunctionType(_, _, _, arg_names, _, _, _)
+                    if parameter_names && !is_decl =>
+                {
+                    let args = call_arguments(&lines, Position::new(*line, u.col_end));
+                    for (arg_pos, name) in args.into_iter().zip(arg_names) {
+                        if !passes_same_name(&lines, arg_pos, name.as_str()) {
+                            hints.push(InlayHint {
+                                position: arg_pos,
+                                label: format!("{}:", name),
+                                kind: INLAY_HINT_KIND_PARAMETER,
+                                padding_right: true,
+                            })
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    hints.retain(|hint| range.start <= hint.position && hint.position <= range.end);
+    hints
+}
 
-        fields.push(DocumentSymbol {
-            name: field_def.name.clone().to_string(),
-            detail: None,
-            kind: SymbolKind::Field,
-            range: field_range,
-            selection_range: field_range,
-            children: None,
-            tags: Some(vec![]),
-            deprecated: Some(false),
-        });
+/// Whether the identifier starting at `pos` in the file split into `lines` is bound by a `let`
+/// whose pattern is not annotated with a type (only the line of the identifier is inspected)
+fn unannotated_let(lines: &[&str], pos: Position) -> bool {
+    let text: Vec<char> = lines
+        .get(pos.line as usize)
+        .unwrap_or(&"")
+        .chars()
+        .collect();
+    // the buffer may have changed since the symbols were computed
+    let col = cmp::min(pos.character as usize, text.len());
+    let is_ident = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+
+    // the last `let` keyword before the identifier
+    let Some(let_col) = (0..col.saturating_sub(3)).rev().find(|i| {
+        text[*i..].starts_with(&['l', 'e', 't'])
+            && (*i == 0 || !is_ident(&text[*i - 1]))
+            && text.get(*i + 3).map_or(false, |c| c.is_whitespace())
+    }) else {
+        return false;
+    };
+
+    // the identifier must be within the pattern (before the `=`), which must not be followed by
+    // an annotation (colons within the pattern, e.g., in struct unpacking, do not count)
+    let mut depth = 0;
+    let mut in_pattern = true;
+    for (i, c) in text.iter().enumerate().skip(let_col + 3) {
+        match c {
+            '(' | '{' | '<' => depth += 1,
+            ')' | '}' | '>' => depth -= 1,
+            ':' if depth == 0 => return false,
+            '=' | ';' if depth == 0 => {
+                in_pattern = i > col;
+                break;
+            }
+            _ => (),
+        }
     }
+    in_pattern
 }
 
-#[cfg(test)]
-fn assert_use_def_with_doc_string(
-    mod_symbols: &UseDefMap,
-    file_name_mapping: &BTreeMap<FileHash, Symbol>,
-    use_idx: usize,
-    use_line: u32,
-    use_col: u32,
-    def_line: u32,
-    def_col: u32,
-    def_file: &str,
-    type_str: &str,
-    type_def: Option<(u32, u32, &str)>,
-    doc_string: &str,
-) {
-    let uses = mod_symbols.get(use_line).unwrap();
-    let use_def = uses.iter().nth(use_idx).unwrap();
-    assert!(use_def.col_start == use_col);
-    assert!(use_def.def_loc.start.line == def_line);
-    assert!(use_def.def_loc.start.character == def_col);
-    assert!(file_name_mapping
-        .get(&use_def.def_loc.fhash)
-        .unwrap()
-        .as_str()
-        .ends_with(def_file));
-    assert!(type_str == format!("{}", use_def.use_type));
+/// Positions of the arguments of the call whose called function's name ends at `pos` in the file
+/// split into `lines` (nothing if the name is not followed by an argument list)
+fn call_arguments(lines: &[&str], pos: Position) -> Vec<Position> {
+    let mut following = lines
+        .iter()
+        .enumerate()
+        .skip(pos.line as usize)
+        .flat_map(|(line, text)| {
+            let skip = if line == pos.line as usize {
+                pos.character as usize
+            } else {
+                0
+            };
+            text.chars()
+                .enumerate()
+                .skip(skip)
+                .map(move |(col, c)| (Position::new(line as u32, col as u32), c))
+        })
+        .peekable();
+
+    // skip whitespace and type arguments between the function's name and its arguments
+    while following.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    if following.next_if(|(_, c)| *c == '<').is_some() {
+        let mut depth = 1;
+        while depth > 0 {
+            match following.next() {
+                Some((_, '<')) => depth += 1,
+                Some((_, '>')) => depth -= 1,
+                Some(_) => (),
+                None => return vec![],
+            }
+        }
+        while following.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+    if following.next_if(|(_, c)| *c == '(').is_none() {
+        return vec![];
+    }
 
-    assert!(doc_string == use_def.doc_string);
-    match use_def.type_def_loc {
-        Some(type_def_loc) => {
-            let tdef_line = type_def.unwrap().0;
-            let tdef_col = type_def.unwrap().1;
-            let tdef_file = type_def.unwrap().2;
-            assert!(type_def_loc.start.line == tdef_line);
-            assert!(type_def_loc.start.character == tdef_col);
-            assert!(file_name_mapping
-                .get(&type_def_loc.fhash)
-                .unwrap()
-                .as_str()
-                .ends_with(tdef_file));
+    let mut args = vec![];
+    let mut depth = 0;
+    let mut next_arg = true;
+    for (arg_pos, c) in following {
+        match c {
+            ')' | ']' | '}' if depth == 0 => break,
+            ',' if depth == 0 => {
+                next_arg = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => (),
+        }
+        if next_arg {
+            args.push(arg_pos);
+            next_arg = false;
         }
-        None => assert!(type_def.is_none()),
     }
+    args
 }
 
-#[cfg(test)]
-fn assert_use_def(
-    mod_symbols: &UseDefMap,
-    file_name_mapping: &BTreeMap<FileHash, Symbol>,
-    use_idx: usize,
-    use_line: u32,
-    use_col: u32,
-    def_line: u32,
-    def_col: u32,
-    def_file: &str,
-    type_str: &str,
-    type_def: Option<(u32, u32, &str)>,
-) {
-    assert_use_def_with_doc_string(
-        mod_symbols,
-        file_name_mapping,
-        use_idx,
-        use_line,
-        use_col,
-        def_line,
-        def_col,
-        def_file,
-        type_str,
-        type_def,
-        "",
-    )
+/// Whether the argument at `pos` in the file split into `lines` is a variable named after the
+/// parameter it is passed to (in which case a parameter name hint would be redundant)
+fn passes_same_name(lines: &[&str], pos: Position, name: &str) -> bool {
+    let text = lines.get(pos.line as usize).unwrap_or(&"");
+    let arg: String = text
+        .chars()
+        .skip(pos.character as usize)
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    arg == name
 }
 
-#[test]
-/// Tests if symbolication + doc_string information for documented Move constructs is constructed correctly.
-fn docstring_test() {
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+/// Handles code action request of the language server
+pub fn on_code_action_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+
+    let uri = parameters.text_document.uri;
+    let fpath = uri.to_file_path().unwrap();
+    let actions: Vec<CodeActionOrCommand> = context
+        .files
+        .get(&fpath)
+        .map(|buffer| import_actions(symbols, &uri, buffer, &parameters.range.start))
+        .into_iter()
+        .flatten()
+        .map(CodeActionOrCommand::CodeAction)
+        .collect();
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), actions);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send code action response: {:?}", err);
+    }
+}
 
-    path.push("tests/symbols");
+/// Computes quick fixes importing the member of another module named by the (unqualified)
+/// identifier at `pos` in `buffer` (the contents of the document at `uri`), one for each module
+/// declaring a function or struct with that name. There are none if the identifier resolves to a
+/// definition already, or if the enclosing module declares or imports a member with that name.
+fn import_actions(symbols: &Symbols, uri: &Url, buffer: &str, pos: &Position) -> Vec<CodeAction> {
+    let Ok(fpath) = uri.to_file_path() else {
+        return vec![];
+    };
+    let resolved = symbols
+        .file_use_defs
+        .get(&fpath)
+        .and_then(|mod_symbols| mod_symbols.get(pos.line))
+        .map_or(false, |uses| {
+            uses.iter()
+                .any(|u| pos.character >= u.col_start && pos.character <= u.col_end)
+        });
+    if resolved {
+        return vec![];
+    }
 
-    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
-    let symbols = symbols_opt.unwrap();
+    let lines: Vec<&str> = buffer.lines().collect();
+    let Some(text) = lines.get(pos.line as usize) else {
+        return vec![];
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let is_ident = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+
+    let col = cmp::min(pos.character as usize, chars.len());
+    let start = (0..col)
+        .rev()
+        .take_while(|i| is_ident(&chars[*i]))
+        .last()
+        .unwrap_or(col);
+    let end = (col..chars.len())
+        .take_while(|i| is_ident(&chars[*i]))
+        .last()
+        .map_or(col, |i| i + 1);
+    let name: String = chars[start..end].iter().collect();
+    // qualified names are resolved through their module (not through a member import)
+    if name.is_empty() || chars[..start].ends_with(&[':', ':']) {
+        return vec![];
+    }
 
-    let mut fpath = path.clone();
-    fpath.push("sources/M6.move");
-    let cpath = dunce::canonicalize(&fpath).unwrap();
+    // the enclosing module (the last one in the file whose name precedes `pos`), whose header is
+    // on the line of its name, and its imports (up to the next module in the file)
+    let mods = symbols.file_mods.get(&fpath).into_iter().flatten();
+    let Some(enclosing) = mods
+        .clone()
+        .filter(|mod_defs| mod_defs.start <= *pos)
+        .max_by_key(|mod_defs| mod_defs.start)
+    else {
+        return vec![];
+    };
+    let header_line = enclosing.start.line as usize;
+    let next_mod_line = mods
+        .map(|mod_defs| mod_defs.start.line as usize)
+        .filter(|line| *line > header_line)
+        .min()
+        .unwrap_or(lines.len());
+    let imports: Vec<(usize, &str)> = lines
+        .iter()
+        .enumerate()
+        .take(next_mod_line)
+        .skip(header_line + 1)
+        .filter(|(_, text)| text.trim_start().starts_with("use "))
+        .map(|(line, text)| (line, *text))
+        .collect();
+    if imports.iter().any(|(_, text)| imports_member(text, &name)) {
+        return vec![];
+    }
 
-    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+    let member = Symbol::from(name.as_str());
+    let mut modules = BTreeSet::new();
+    for mod_defs in symbols.file_mods.values().flatten() {
+        let candidate = format!(
+            "{}::{}",
+            addr_to_ide_string(&mod_defs.name.address),
+            mod_defs.name.module.value()
+        );
+        let declares =
+            mod_defs.functions.contains_key(&member) || mod_defs.structs.contains_key(&member);
+        if mod_defs.name == enclosing.name {
+            if declares {
+                return vec![];
+            }
+        } else if declares {
+            modules.insert(candidate);
+        }
+    }
 
-    // struct def name
-    assert_use_def_with_doc_string(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        4,
-        11,
-        4,
-        11,
-        "M6.move",
-        "Symbols::M6::DocumentedStruct",
-        Some((4, 11, "M6.move")),
-        "This is a documented struct\nWith a multi-line docstring\n",
-    );
+    // insert the import after the imports at the top of the module, or right after its header
+    let leading_imports = imports
+        .iter()
+        .zip(header_line + 1..)
+        .take_while(|((line, _), expected)| line == expected)
+        .last();
+    let (insert_line, indent) = match leading_imports {
+        Some(((line, text), _)) => (
+            *line + 1,
+            text[..text.len() - text.trim_start().len()].to_string(),
+        ),
+        None => {
+            let header = lines.get(header_line).copied().unwrap_or("");
+            let header_indent = &header[..header.len() - header.trim_start().len()];
+            (header_line + 1, format!("{}    ", header_indent))
+        }
+    };
+    let insert_pos = Position::new(insert_line as u32, 0);
+
+    modules
+        .into_iter()
+        .map(|module| {
+            let import = format!("{}::{}", module, name);
+            let edit = TextEdit::new(
+                Range::new(insert_pos, insert_pos),
+                format!("{}use {};\n", indent, import),
+            );
+            CodeAction {
+                title: format!("Import `{}`", import),
+                kind: Some(CodeActionKind::QUICKFIX),
+                edit: Some(WorkspaceEdit::new(HashMap::from([(
+                    uri.clone(),
+                    vec![edit],
+                )]))),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Whether the `use` declaration on the line `text` imports a module member called `name` (under
+/// that name)
+fn imports_member(text: &str, name: &str) -> bool {
+    let decl = text.trim().trim_start_matches("use ").trim_end_matches(';');
+    let members = match (decl.find('{'), decl.rfind('}')) {
+        (Some(open), Some(close)) if open < close => decl[open + 1..close].split(',').collect(),
+        _ => vec![decl.rsplit("::").next().unwrap_or("")],
+    };
+    members.into_iter().any(|member| {
+        let imported = match member.split_once(" as ") {
+            Some((_, alias)) => alias,
+            None => member,
+        };
+        imported.trim() == name
+    })
+}
+
+/// Handles hover request of the language server
+pub fn on_hover_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<HoverParams>(request.params.clone())
+        .expect("could not deserialize hover request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position_params.position;
+    let line = loc.line;
+    let col = loc.character;
+
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        line,
+        col,
+        request.id.clone(),
+        |u| {
+            let header = match &u.description {
+                Some(description) => description.clone(),
+                None => format!("{}", u.use_type),
+            };
+            let lang_string = LanguageString {
+                language: "".to_string(),
+                value: if !u.doc_string.is_empty() {
+                    format!("{}\n\n{}", header, u.doc_string)
+                } else {
+                    header
+                },
+            };
+            let contents = HoverContents::Scalar(MarkedString::LanguageString(lang_string));
+            let range = None;
+            Some(serde_json::to_value(Hover { contents, range }).unwrap())
+        },
+    );
+}
+
+/// Helper function to handle language server queries related to identifier uses
+pub fn on_use_request(
+    context: &Context,
+    symbols: &Symbols,
+    use_fpath: &PathBuf,
+    use_line: u32,
+    use_col: u32,
+    id: RequestId,
+    use_def_action: impl Fn(&UseDef) -> Option<serde_json::Value>,
+) {
+    let mut result = None;
+
+    let mut use_def_found = false;
+    if let Some(mod_symbols) = symbols.file_use_defs.get(use_fpath) {
+        if let Some(uses) = mod_symbols.get(use_line) {
+            for u in uses {
+                if use_col >= u.col_start && use_col <= u.col_end {
+                    result = use_def_action(&u);
+                    use_def_found = true;
+                }
+            }
+        }
+    }
+    if !use_def_found {
+        result = Some(serde_json::to_value(Option::<lsp_types::Location>::None).unwrap());
+    }
+
+    eprintln!("about to send use response");
+    // unwrap will succeed based on the logic above which the compiler is unable to figure out
+    // without using Option
+    let response = lsp_server::Response::new_ok(id, result.unwrap());
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send use response: {:?}", err);
+    }
+}
+
+/// Handles document symbol request of the language server
+#[allow(deprecated)]
+pub fn on_document_symbol_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<DocumentSymbolParams>(request.params.clone())
+        .expect("could not deserialize document symbol request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    eprintln!("on_document_symbol_request: {:?}", fpath);
+
+    let empty_mods: BTreeSet<ModuleDefs> = BTreeSet::new();
+    let mods = symbols.file_mods.get(&fpath).unwrap_or(&empty_mods);
+
+    let mut defs: Vec<DocumentSymbol> = vec![];
+    for mod_def in mods {
+        let name = mod_def.name.module.clone().to_string();
+        let detail = Some(mod_def.name.clone().to_string());
+        let kind = SymbolKind::Module;
+        let range = Range {
+            start: mod_def.start,
+            end: mod_def.start,
+        };
+
+        let mut children = vec![];
+
+        // handle constants
+        let cloned_const_def = mod_def.constants.clone();
+        for (sym, const_def_pos) in cloned_const_def {
+            let const_range = Range {
+                start: const_def_pos,
+                end: const_def_pos,
+            };
+
+            children.push(DocumentSymbol {
+                name: sym.clone().to_string(),
+                detail: None,
+                kind: SymbolKind::Constant,
+                range: const_range,
+                selection_range: const_range,
+                children: None,
+                tags: Some(vec![]),
+                deprecated: Some(false),
+            });
+        }
+
+        // handle structs
+        let cloned_struct_def = mod_def.structs.clone();
+        for (sym, struct_def) in cloned_struct_def {
+            let struct_range = Range {
+                start: struct_def.name_start,
+                end: struct_def.name_start,
+            };
+
+            let mut fields: Vec<DocumentSymbol> = vec![];
+            handle_struct_fields(struct_def, &mut fields);
+
+            children.push(DocumentSymbol {
+                name: sym.clone().to_string(),
+                detail: None,
+                kind: SymbolKind::Struct,
+                range: struct_range,
+                selection_range: struct_range,
+                children: Some(fields),
+                tags: Some(vec![]),
+                deprecated: Some(false),
+            });
+        }
+
+        // handle functions
+        let cloned_func_def = mod_def.functions.clone();
+        for (sym, func_def) in cloned_func_def {
+            let func_range = Range {
+                start: func_def.start,
+                end: func_def.start,
+            };
+
+            let mut detail = None;
+            if !func_def.attrs.is_empty() {
+                detail = Some(format!("{:?}", func_def.attrs));
+            }
+
+            children.push(DocumentSymbol {
+                name: sym.clone().to_string(),
+                detail,
+                kind: SymbolKind::Function,
+                range: func_range,
+                selection_range: func_range,
+                children: None,
+                tags: Some(vec![]),
+                deprecated: Some(false),
+            });
+        }
+
+        defs.push(DocumentSymbol {
+            name,
+            detail,
+            kind,
+            range,
+            selection_range: range,
+            children: Some(children),
+            tags: Some(vec![]),
+            deprecated: Some(false),
+        });
+    }
+
+    // unwrap will succeed based on the logic above which the compiler is unable to figure out
+    let response = lsp_server::Response::new_ok(request.id.clone(), defs);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send use response: {:?}", err);
+    }
+}
+
+/// Handles folding range request of the language server
+pub fn on_folding_range_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<FoldingRangeParams>(request.params.clone())
+        .expect("could not deserialize folding range request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    eprintln!("on_folding_range_request: {:?}", fpath);
+
+    let ranges = symbols
+        .file_folding_ranges
+        .get(&fpath)
+        .cloned()
+        .unwrap_or_default();
+
+    let response = lsp_server::Response::new_ok(request.id.clone(), ranges);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send folding range response: {:?}", err);
+    }
+}
+
+/// Helper function to handle struct fields
+#[allow(deprecated)]
+fn handle_struct_fields(struct_def: StructDef, fields: &mut Vec<DocumentSymbol>) {
+    let clonded_fileds = struct_def.field_defs;
+
+    for field_def in clonded_fileds {
+        let field_range = Range {
+            start: field_def.start,
+            end: field_def.start,
+        };
+
+        fields.push(DocumentSymbol {
+            name: field_def.name.clone().to_string(),
+            detail: None,
+            kind: SymbolKind::Field,
+            range: field_range,
+            selection_range: field_range,
+            children: None,
+            tags: Some(vec![]),
+            deprecated: Some(false),
+        });
+    }
+}
+
+/// State of a workspace symbol request. The workspace is scanned one file at a time (see
+/// `WorkspaceSymbolScan::step`) so that the server can process other messages between files, and in
+/// particular abandon the scan if the request gets cancelled or superseded by a newer query. If the
+/// client provided a partial result token, matches found in each file are streamed to the client as
+/// soon as the file is scanned.
+pub struct WorkspaceSymbolScan {
+    /// ID of the request being answered
+    id: RequestId,
+    /// Token used to report partial results (if the client supports them)
+    partial_result_token: Option<ProgressToken>,
+    /// Lower-case query string
+    query: String,
+    /// Files that remain to be scanned
+    pending_files: VecDeque<PathBuf>,
+    /// Matches accumulated so far (only used if results are not streamed)
+    results: Vec<SymbolInformation>,
+    /// Maximum number of matches to report (0 meaning no limit)
+    max_results: usize,
+    /// Whether to report test functions
+    include_tests: bool,
+    /// Number of matches reported so far
+    num_results: usize,
+    /// Whether some matches were dropped to stay within the limit
+    truncated: bool,
+}
+
+impl WorkspaceSymbolScan {
+    /// Starts handling a workspace symbol request of the language server
+    pub fn new(request: &Request, symbols: &Symbols, config: &Config) -> Self {
+        let parameters = serde_json::from_value::<WorkspaceSymbolParams>(request.params.clone())
+            .expect("could not deserialize workspace symbol request");
+
+        eprintln!("on_workspace_symbol_request: {:?}", parameters.query);
+
+        Self {
+            id: request.id.clone(),
+            partial_result_token: parameters.partial_result_params.partial_result_token,
+            query: parameters.query.to_lowercase(),
+            pending_files: symbols.file_mods.keys().cloned().collect(),
+            results: vec![],
+            max_results: config.max_results,
+            include_tests: config.workspace_symbols_include_tests,
+            num_results: 0,
+            truncated: false,
+        }
+    }
+
+    /// ID of the request this scan is answering
+    pub fn id(&self) -> &RequestId {
+        &self.id
+    }
+
+    /// Scans the next pending file, sending its matches to the client as a partial result if
+    /// possible. Once all files have been scanned, sends the final response to the client and
+    /// returns true.
+    pub fn step(&mut self, context: &Context, symbols: &Symbols) -> bool {
+        if let Some(fpath) = self.pending_files.pop_front() {
+            let mut matches = self.file_matches(&fpath, symbols);
+            if self.max_results != 0 && matches.len() > self.max_results - self.num_results {
+                // no need to look any further
+                matches.truncate(self.max_results - self.num_results);
+                self.pending_files.clear();
+                self.truncated = true;
+            }
+            self.num_results += matches.len();
+            if matches.is_empty() {
+                return false;
+            }
+            match &self.partial_result_token {
+                Some(token) => {
+                    let params = serde_json::json!({ "token": token, "value": matches });
+                    let notification = Notification::new(Progress::METHOD.to_string(), params);
+                    if let Err(err) = context
+                        .connection
+                        .sender
+                        .send(lsp_server::Message::Notification(notification))
+                    {
+                        eprintln!("could not send workspace symbol partial result: {:?}", err);
+                    }
+                }
+                None => self.results.extend(matches),
+            }
+            return false;
+        }
+
+        if self.truncated {
+            notify_truncated_results(context, "workspace symbols", self.max_results);
+        }
+        // if partial results were streamed, the final response must not repeat them
+        let response =
+            lsp_server::Response::new_ok(self.id.clone(), std::mem::take(&mut self.results));
+        if let Err(err) = context
+            .connection
+            .sender
+            .send(lsp_server::Message::Response(response))
+        {
+            eprintln!("could not send workspace symbol response: {:?}", err);
+        }
+        true
+    }
+
+    /// Abandons the scan, letting the client know that the request has been cancelled
+    pub fn cancel(self, context: &Context) {
+        let response = lsp_server::Response::new_err(
+            self.id,
+            ErrorCode::RequestCanceled as i32,
+            "workspace symbol request cancelled".to_string(),
+        );
+        if let Err(err) = context
+            .connection
+            .sender
+            .send(lsp_server::Message::Response(response))
+        {
+            eprintln!("could not send workspace symbol cancellation: {:?}", err);
+        }
+    }
+
+    /// Returns module members (and modules) defined in a given file whose names contain the query
+    /// string (ignoring case)
+    #[allow(deprecated)]
+    fn file_matches(&self, fpath: &Path, symbols: &Symbols) -> Vec<SymbolInformation> {
+        let mut matches = vec![];
+        let mods = match symbols.file_mods.get(fpath) {
+            Some(mods) => mods,
+            None => return matches,
+        };
+        let uri = match Url::from_file_path(fpath) {
+            Ok(uri) => uri,
+            Err(()) => return matches,
+        };
+
+        let mut add_match =
+            |name: String, kind: SymbolKind, pos: Position, container_name: Option<String>| {
+                if !name.to_lowercase().contains(&self.query) {
+                    return;
+                }
+                matches.push(SymbolInformation {
+                    name,
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    location: Location {
+                        uri: uri.clone(),
+                        range: Range {
+                            start: pos,
+                            end: pos,
+                        },
+                    },
+                    container_name,
+                });
+            };
+
+        for mod_def in mods {
+            let mod_name = mod_def.name.to_string();
+            add_match(
+                mod_def.name.module.to_string(),
+                SymbolKind::Module,
+                mod_def.start,
+                None,
+            );
+            for (sym, const_def_pos) in &mod_def.constants {
+                add_match(
+                    sym.to_string(),
+                    SymbolKind::Constant,
+                    *const_def_pos,
+                    Some(mod_name.clone()),
+                );
+            }
+            for (sym, struct_def) in &mod_def.structs {
+                add_match(
+                    sym.to_string(),
+                    SymbolKind::Struct,
+                    struct_def.name_start,
+                    Some(mod_name.clone()),
+                );
+            }
+            for (sym, func_def) in &mod_def.functions {
+                if !self.include_tests && is_test_function(func_def) {
+                    continue;
+                }
+                add_match(
+                    sym.to_string(),
+                    SymbolKind::Function,
+                    func_def.start,
+                    Some(mod_name.clone()),
+                );
+            }
+            // schemas are reported as structs, as in semantic tokens
+            for (sym, schema_def_pos) in &mod_def.schemas {
+                add_match(
+                    sym.to_string(),
+                    SymbolKind::Struct,
+                    *schema_def_pos,
+                    Some(mod_name.clone()),
+                );
+            }
+        }
+        matches
+    }
+}
+
+/// Checks if a function is only meant to be compiled for testing
+fn is_test_function(func_def: &FunctionDef) -> bool {
+    func_def
+        .attrs
+        .iter()
+        .any(|a| a == TestingAttribute::TEST || a == TestingAttribute::TEST_ONLY)
+}
+
+#[cfg(test)]
+fn assert_use_def_with_doc_string(
+    mod_symbols: &UseDefMap,
+    file_name_mapping: &BTreeMap<FileHash, Symbol>,
+    use_idx: usize,
+    use_line: u32,
+    use_col: u32,
+    def_line: u32,
+    def_col: u32,
+    def_file: &str,
+    type_str: &str,
+    type_def: Option<(u32, u32, &str)>,
+    doc_string: &str,
+) {
+    let uses = mod_symbols.get(use_line).unwrap();
+    let use_def = uses.iter().nth(use_idx).unwrap();
+    assert!(use_def.col_start == use_col);
+    assert!(use_def.def_loc.start.line == def_line);
+    assert!(use_def.def_loc.start.character == def_col);
+    assert!(file_name_mapping
+        .get(&use_def.def_loc.fhash)
+        .unwrap()
+        .as_str()
+        .ends_with(def_file));
+    assert!(type_str == format!("{}", use_def.use_type));
+
+    assert!(doc_string == use_def.doc_string);
+    match use_def.type_def_loc {
+        Some(type_def_loc) => {
+            let tdef_line = type_def.unwrap().0;
+            let tdef_col = type_def.unwrap().1;
+            let tdef_file = type_def.unwrap().2;
+            assert!(type_def_loc.start.line == tdef_line);
+            assert!(type_def_loc.start.character == tdef_col);
+            assert!(file_name_mapping
+                .get(&type_def_loc.fhash)
+                .unwrap()
+                .as_str()
+                .ends_with(tdef_file));
+        }
+        None => assert!(type_def.is_none()),
+    }
+}
+
+#[cfg(test)]
+fn assert_use_def(
+    mod_symbols: &UseDefMap,
+    file_name_mapping: &BTreeMap<FileHash, Symbol>,
+    use_idx: usize,
+    use_line: u32,
+    use_col: u32,
+    def_line: u32,
+    def_col: u32,
+    def_file: &str,
+    type_str: &str,
+    type_def: Option<(u32, u32, &str)>,
+) {
+    assert_use_def_with_doc_string(
+        mod_symbols,
+        file_name_mapping,
+        use_idx,
+        use_line,
+        use_col,
+        def_line,
+        def_col,
+        def_file,
+        type_str,
+        type_def,
+        "",
+    )
+}
+
+#[test]
+/// Tests if symbolication + doc_string information for documented Move constructs is constructed correctly.
+fn docstring_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M6.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // struct def name
+    assert_use_def_with_doc_string(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        0,
+        4,
+        11,
+        4,
+        11,
+        "M6.move",
+        "Symbols::M6::DocumentedStruct",
+        Some((4, 11, "M6.move")),
+        "This is a documented struct\nWith a multi-line docstring\n",
+    );
 
     // const def name
     assert_use_def_with_doc_string(
@@ -3311,369 +5444,1380 @@ fn symbols_test() {
         "u128",
         None,
     );
-    // constant in an annotation (annot function)
+    // constant in an annotation (annot function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        118,
+        19,
+        6,
+        10,
+        "M1.move",
+        "u64",
+        None,
+    );
+    // struct type param def (struct_param function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        122,
+        21,
+        122,
+        21,
+        "M1.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
+    );
+    // struct type param use (struct_param function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        0,
+        123,
+        8,
+        122,
+        21,
+        "M1.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
+    );
+    // struct type local var def (struct_var function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        0,
+        127,
+        12,
+        127,
+        12,
+        "M1.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
+    );
+    // struct type local var use (struct_var function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        0,
+        129,
+        12,
+        127,
+        12,
+        "M1.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
+    );
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M3.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // generic type in struct definition
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        2,
+        23,
+        2,
+        23,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in struct field definition
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        3,
+        20,
+        2,
+        23,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in generic type definition (type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        6,
+        23,
+        6,
+        23,
+        "M3.move",
+        "T",
+        None,
+    );
+    // parameter (type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        2,
+        6,
+        39,
+        6,
+        39,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in param type (type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        3,
+        6,
+        46,
+        6,
+        23,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in return type (type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        4,
+        6,
+        50,
+        6,
+        23,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in struct param type (struct_type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        4,
+        10,
+        52,
+        10,
+        30,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in struct return type (struct_type_param_arg function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        6,
+        10,
+        69,
+        10,
+        30,
+        "M3.move",
+        "T",
+        None,
+    );
+    // generic type in pack (pack_type_param function)
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        15,
+        20,
+        14,
+        24,
+        "M3.move",
+        "T",
+        None,
+    );
+    // field type in struct field definition which itself is a struct
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        1,
+        23,
+        20,
+        2,
+        11,
+        "M3.move",
+        "Symbols::M3::ParamStruct<T>",
+        Some((2, 11, "M3.move")),
+    );
+    // generic type in struct field definition which itself is a struct
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        2,
+        23,
+        32,
+        22,
+        30,
+        "M3.move",
+        "T",
+        None,
+    );
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M4.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // param name in RHS (if_cond function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         1,
-        118,
-        19,
-        6,
-        10,
-        "M1.move",
+        4,
+        18,
+        2,
+        16,
+        "M4.move",
         "u64",
         None,
     );
-    // struct type param def (struct_param function)
+    // param name in RHS (if_cond function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         1,
-        122,
-        21,
-        122,
-        21,
-        "M1.move",
-        "Symbols::M2::SomeOtherStruct",
-        Some((2, 11, "M2.move")),
+        6,
+        22,
+        4,
+        12,
+        "M4.move",
+        "u64",
+        None,
     );
-    // struct type param use (struct_param function)
+    // var in if's true branch (if_cond function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         0,
-        123,
-        8,
-        122,
-        21,
-        "M1.move",
-        "Symbols::M2::SomeOtherStruct",
-        Some((2, 11, "M2.move")),
+        7,
+        12,
+        4,
+        12,
+        "M4.move",
+        "u64",
+        None,
     );
-    // struct type local var def (struct_var function)
+    // redefined var in if's false branch (if_cond function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         0,
-        127,
-        12,
-        127,
+        10,
         12,
-        "M1.move",
-        "Symbols::M2::SomeOtherStruct",
-        Some((2, 11, "M2.move")),
+        9,
+        16,
+        "M4.move",
+        "u64",
+        None,
     );
-    // struct type local var use (struct_var function)
+    // var name in while loop condition (while_loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         0,
-        129,
-        12,
-        127,
+        20,
+        15,
+        18,
         12,
-        "M1.move",
-        "Symbols::M2::SomeOtherStruct",
-        Some((2, 11, "M2.move")),
+        "M4.move",
+        "u64",
+        None,
     );
-
-    let mut fpath = path.clone();
-    fpath.push("sources/M3.move");
-    let cpath = dunce::canonicalize(&fpath).unwrap();
-
-    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
-
-    // generic type in struct definition
+    // var name in while loop's inner block (while_loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         1,
-        2,
-        23,
-        2,
         23,
-        "M3.move",
-        "T",
+        26,
+        18,
+        12,
+        "M4.move",
+        "u64",
         None,
     );
-    // generic type in struct field definition
+    // redefined var name in while loop's inner block (while_loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         1,
-        3,
-        20,
-        2,
+        24,
         23,
-        "M3.move",
-        "T",
+        23,
+        20,
+        "M4.move",
+        "u64",
         None,
     );
-    // generic type in generic type definition (type_param_arg function)
+    // var name in while loop's main block (while_loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        1,
-        6,
-        23,
-        6,
-        23,
-        "M3.move",
-        "T",
+        0,
+        26,
+        12,
+        18,
+        12,
+        "M4.move",
+        "u64",
         None,
     );
-    // parameter (type_param_arg function)
+    // redefined var name in while loop's inner block (loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        2,
-        6,
-        39,
-        6,
+        1,
+        40,
+        23,
         39,
-        "M3.move",
-        "T",
+        20,
+        "M4.move",
+        "u64",
         None,
     );
-    // generic type in param type (type_param_arg function)
+    // var name in loop's main block (loop function)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        3,
-        6,
-        46,
-        6,
-        23,
-        "M3.move",
-        "T",
+        0,
+        43,
+        16,
+        34,
+        12,
+        "M4.move",
+        "u64",
         None,
     );
-    // generic type in return type (type_param_arg function)
+    // const in a different module in the same file
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        4,
-        6,
-        50,
-        6,
-        23,
-        "M3.move",
-        "T",
+        0,
+        55,
+        10,
+        55,
+        10,
+        "M4.move",
+        "u64",
         None,
     );
-    // generic type in struct param type (struct_type_param_arg function)
+}
+
+#[test]
+/// Tests if workspace symbol results are streamed to the client as partial results (one chunk per
+/// scanned file) followed by an empty final response.
+fn workspace_symbol_partial_results_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let (server, client) = lsp_server::Connection::memory();
+    let context = Context {
+        connection: server,
+        files: crate::vfs::VirtualFileSystem::default(),
+        symbols: Arc::new(Mutex::new(Symbolicator::empty_symbols())),
+        config: crate::context::Config::default(),
+    };
+
+    let token = ProgressToken::String("workspace-symbols".to_string());
+    let params = WorkspaceSymbolParams {
+        partial_result_params: lsp_types::PartialResultParams {
+            partial_result_token: Some(token.clone()),
+        },
+        work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+        // matches structs (and functions) defined in different files
+        query: "struct".to_string(),
+    };
+    let request = Request::new(
+        RequestId::from(42),
+        lsp_types::request::WorkspaceSymbol::METHOD.to_string(),
+        params,
+    );
+
+    let config = crate::context::Config {
+        max_results: 0,
+        ..Default::default()
+    };
+    let mut scan = WorkspaceSymbolScan::new(&request, &symbols, &config);
+    while !scan.step(&context, &symbols) {}
+
+    let mut chunks = vec![];
+    let mut final_response = None;
+    while let Ok(message) = client.receiver.try_recv() {
+        assert!(
+            final_response.is_none(),
+            "message sent after the final response"
+        );
+        match message {
+            lsp_server::Message::Notification(n) => {
+                assert_eq!(n.method, Progress::METHOD);
+                assert_eq!(n.params["token"], serde_json::to_value(&token).unwrap());
+                let chunk =
+                    serde_json::from_value::<Vec<SymbolInformation>>(n.params["value"].clone())
+                        .unwrap();
+                assert!(!chunk.is_empty());
+                chunks.push(chunk);
+            }
+            lsp_server::Message::Response(r) => final_response = Some(r),
+            lsp_server::Message::Request(r) => panic!("unexpected request {:?}", r),
+        }
+    }
+
+    assert!(chunks.len() > 1, "expected multiple partial result chunks");
+    // each chunk contains matches from a single file
+    for chunk in &chunks {
+        assert!(chunk
+            .iter()
+            .all(|s| s.location.uri == chunk[0].location.uri));
+    }
+    let names: BTreeSet<String> = chunks.iter().flatten().map(|s| s.name.clone()).collect();
+    for expected in [
+        "SomeStruct",
+        "SomeOtherStruct",
+        "ParamStruct",
+        "OtherDocStruct",
+    ] {
+        assert!(names.contains(expected), "missing {}", expected);
+    }
+
+    let final_response = final_response.expect("no final response");
+    assert_eq!(final_response.id, RequestId::from(42));
+    assert_eq!(
+        final_response.result,
+        Some(serde_json::to_value(Vec::<SymbolInformation>::new()).unwrap())
+    );
+}
+
+#[test]
+/// Tests if spec schemas are matched by workspace symbol queries.
+fn workspace_symbol_schema_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M9.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let params = WorkspaceSymbolParams {
+        partial_result_params: lsp_types::PartialResultParams::default(),
+        work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+        query: "abortsif".to_string(),
+    };
+    let request = Request::new(
+        RequestId::from(42),
+        lsp_types::request::WorkspaceSymbol::METHOD.to_string(),
+        params,
+    );
+    let scan = WorkspaceSymbolScan::new(&request, &symbols, &crate::context::Config::default());
+
+    let matches = scan.file_matches(&cpath, &symbols);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].name, "AbortsIfZero");
+    assert_eq!(matches[0].kind, SymbolKind::Struct);
+    assert_eq!(matches[0].location.range.start, Position::new(2, 16));
+}
+
+#[test]
+/// Tests if references are capped at the configured maximum number of results.
+fn references_max_results_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+    // struct def name (SomeStruct is used many times in the same module)
+    let struct_def = mod_symbols
+        .get(2)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.col_start == 11)
+        .unwrap();
+
+    let (all_refs, truncated) = references(&symbols, &struct_def.def_loc, true, 0);
+    assert!(!truncated);
+    assert!(all_refs.len() > 3);
+
+    let (refs, truncated) = references(&symbols, &struct_def.def_loc, true, 3);
+    assert!(truncated);
+    assert_eq!(refs.len(), 3);
+
+    let (refs, truncated) = references(&symbols, &struct_def.def_loc, true, all_refs.len());
+    assert!(!truncated);
+    assert_eq!(refs, all_refs);
+}
+
+#[test]
+/// Tests if constants used as abort codes (in `abort` and `assert!`) resolve to their declarations.
+fn abort_code_const_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M8.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // const used as an abort code
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        4,
-        10,
-        52,
+        0,
+        7,
+        14,
+        2,
         10,
-        30,
-        "M3.move",
-        "T",
+        "M8.move",
+        "u64",
         None,
     );
-    // generic type in struct return type (struct_type_param_arg function)
+    // const used as an abort code in assert! (after the use of the condition parameter)
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        6,
-        10,
-        69,
+        1,
+        11,
+        19,
+        4,
         10,
-        30,
-        "M3.move",
-        "T",
+        "M8.move",
+        "u64",
         None,
     );
-    // generic type in pack (pack_type_param function)
+}
+
+#[test]
+/// Tests symbolication of names in `use` declarations (and their aliases).
+fn use_alias_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M8.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // aliased struct name
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
-        1,
-        15,
-        20,
+        0,
         14,
-        24,
-        "M3.move",
-        "T",
-        None,
+        22,
+        2,
+        11,
+        "M2.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
     );
-    // field type in struct field definition which itself is a struct
+    // alias of the struct
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         1,
-        23,
-        20,
+        14,
+        41,
         2,
         11,
-        "M3.move",
-        "Symbols::M3::ParamStruct<T>",
-        Some((2, 11, "M3.move")),
+        "M2.move",
+        "Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
     );
-    // generic type in struct field definition which itself is a struct
+    // function name
     assert_use_def(
         mod_symbols,
         &symbols.file_name_mapping,
         2,
-        23,
-        32,
-        22,
-        30,
-        "M3.move",
-        "T",
+        14,
+        48,
+        6,
+        15,
+        "M2.move",
+        "fun Symbols::M2::some_other_struct(v: u64): Symbols::M2::SomeOtherStruct",
+        Some((2, 11, "M2.move")),
+    );
+
+    let uses = mod_symbols.get(14).unwrap();
+    let aliases: Vec<_> = uses
+        .iter()
+        .map(|u| u.description.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        aliases,
+        vec![
+            "alias Other = Symbols::M2::SomeOtherStruct\nstruct Symbols::M2::SomeOtherStruct",
+            "alias Other = Symbols::M2::SomeOtherStruct\nstruct Symbols::M2::SomeOtherStruct",
+            "alias some_other_struct = Symbols::M2::some_other_struct\n\
+             fun Symbols::M2::some_other_struct(v: u64): Symbols::M2::SomeOtherStruct",
+        ]
+    );
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // `Self` aliasing the module
+    let uses = mod_symbols.get(28).unwrap();
+    let use_def = uses.iter().next().unwrap();
+    assert!(use_def.col_start == 22);
+    assert!(use_def.def_loc.start.line == 0);
+    assert!(use_def.def_loc.start.character == 16);
+    assert_eq!(
+        use_def.description.as_deref(),
+        Some("alias M2 = Symbols::M2\nmodule Symbols::M2")
+    );
+}
+
+#[test]
+/// Tests if all places where a spec schema is included are found from the schema's declaration.
+fn schema_include_references_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M9.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // schema declaration
+    let uses = mod_symbols.get(2).unwrap();
+    let use_def = uses.iter().next().unwrap();
+    assert!(use_def.col_start == 16);
+    assert_eq!(
+        use_def.description.as_deref(),
+        Some("schema Symbols::M9::AbortsIfZero")
+    );
+
+    let (locs, truncated) = references(&symbols, &use_def.def_loc, false, 0);
+    assert!(!truncated);
+    let include_sites: Vec<_> = locs
+        .iter()
+        .map(|loc| (loc.range.start.line, loc.range.start.character))
+        .collect();
+    assert_eq!(include_sites, vec![(12, 16), (20, 16), (28, 26)]);
+}
+
+#[test]
+/// Tests if symbols used inside test functions are resolved, and if test functions can be excluded
+/// from workspace symbol results.
+fn test_function_symbols_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M10.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // test-only helper called from a test function
+    assert_use_def(
+        mod_symbols,
+        &symbols.file_name_mapping,
+        0,
+        9,
+        16,
+        3,
+        8,
+        "M10.move",
+        "fun Symbols::M10::helper(x: u64): u64",
         None,
     );
 
+    let scan_names = |include_tests| {
+        let (server, client) = lsp_server::Connection::memory();
+        let context = Context {
+            connection: server,
+            files: crate::vfs::VirtualFileSystem::default(),
+            symbols: Arc::new(Mutex::new(Symbolicator::empty_symbols())),
+            config: crate::context::Config::default(),
+        };
+        let params = WorkspaceSymbolParams {
+            partial_result_params: lsp_types::PartialResultParams::default(),
+            work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+            query: "helper".to_string(),
+        };
+        let request = Request::new(
+            RequestId::from(42),
+            lsp_types::request::WorkspaceSymbol::METHOD.to_string(),
+            params,
+        );
+        let config = crate::context::Config {
+            workspace_symbols_include_tests: include_tests,
+            ..Default::default()
+        };
+        let mut scan = WorkspaceSymbolScan::new(&request, &symbols, &config);
+        while !scan.step(&context, &symbols) {}
+        let response = match client.receiver.try_recv().unwrap() {
+            lsp_server::Message::Response(r) => r,
+            message => panic!("unexpected message {:?}", message),
+        };
+        serde_json::from_value::<Vec<SymbolInformation>>(response.result.unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect::<BTreeSet<_>>()
+    };
+
+    assert_eq!(
+        scan_names(true),
+        BTreeSet::from(["helper".to_string(), "helper_test".to_string()])
+    );
+    assert!(scan_names(false).is_empty());
+}
+
+#[test]
+/// Tests if linked editing ranges of a local variable include its declaration and all its uses.
+fn linked_editing_ranges_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+    // use of a function parameter (cp function)
+    let value_use = mod_symbols
+        .get(15)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.col_start == 18)
+        .unwrap();
+
+    let ranges = linked_editing_ranges(&symbols, &value_use.def_loc, &cpath).unwrap();
+    let range = |line, col_start, col_end| Range {
+        start: Position {
+            line,
+            character: col_start,
+        },
+        end: Position {
+            line,
+            character: col_end,
+        },
+    };
+    assert_eq!(ranges.ranges, vec![range(14, 11, 16), range(15, 18, 23)]);
+
+    // SomeOtherStruct is defined in M2 so its uses in M1 cannot be edited on their own
+    let struct_use = mod_symbols
+        .get(24)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.col_start == 41)
+        .unwrap();
+    assert!(linked_editing_ranges(&symbols, &struct_use.def_loc, &cpath).is_none());
+}
+
+#[test]
+/// Tests if hovering over a struct definition shows its full declaration.
+fn struct_declaration_hover_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M11.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // generic struct definition
+    let uses = mod_symbols.get(2).unwrap();
+    let use_def = uses.iter().find(|u| u.col_start == 11).unwrap();
+    assert_eq!(
+        use_def.description.as_deref(),
+        Some(
+            "struct Symbols::M11::Table<K: copy + drop, V: store> has store {\n    \
+             keys: vector<K>,\n    \
+             values: vector<V>,\n\
+             }"
+        )
+    );
+}
+
+#[test]
+/// Tests if peeking at a definition returns the requested number of lines around it.
+fn peek_definition_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M10.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // call of the helper function, defined on line 3
+    let uses = mod_symbols.get(9).unwrap();
+    let use_def = uses.iter().find(|u| u.col_start == 16).unwrap();
+
+    let peek = peek_definition(&symbols, &use_def.def_loc, 1).unwrap();
+    assert_eq!(peek.location.range.start, Position::new(3, 8));
+    assert_eq!(peek.first_line, 2);
+    assert_eq!(
+        peek.lines,
+        vec![
+            "    #[test_only]",
+            "    fun helper(x: u64): u64 {",
+            "        x + 1"
+        ]
+    );
+
+    // context is cut short at the beginning of the file
+    let peek = peek_definition(&symbols, &use_def.def_loc, 5).unwrap();
+    assert_eq!(peek.first_line, 0);
+    assert_eq!(peek.lines.len(), 9);
+    assert_eq!(peek.lines[0], "module Symbols::M10 {");
+
+    // the whole file for (too) many context lines
+    let peek = peek_definition(&symbols, &use_def.def_loc, u32::MAX).unwrap();
+    assert_eq!(peek.first_line, 0);
+    assert_eq!(
+        peek.lines.len(),
+        std::fs::read_to_string(&cpath).unwrap().lines().count()
+    );
+}
+
+#[test]
+/// Tests if uses of definitions from a dependency package are resolved to the dependency's sources.
+fn dependency_definition_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M12.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // call of a function from MoveStdlib
+    let uses = mod_symbols.get(4).unwrap();
+    let use_def = uses.iter().find(|u| u.col_start == 16).unwrap();
+    assert_eq!(use_def.def_loc.start, Position::new(21, 22));
+
+    let def_path = symbols
+        .file_name_mapping
+        .get(&use_def.def_loc.fhash)
+        .unwrap();
+    let mut stdlib_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    stdlib_path.push("../move-stdlib/sources/vector.move");
+    assert_eq!(
+        dunce::canonicalize(def_path.as_str()).unwrap(),
+        dunce::canonicalize(stdlib_path).unwrap()
+    );
+    assert!(Url::from_file_path(def_path.as_str()).is_ok());
+}
+
+#[test]
+/// Tests if the type definition of a variable whose type is declared in another file is reported
+/// in that file.
+fn type_definition_other_file_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M12.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+
+    // use of a parameter whose type is defined in M2
+    let uses = mod_symbols.get(8).unwrap();
+    let use_def = uses.iter().find(|u| u.col_start == 8).unwrap();
+    let loc = def_ide_location(&symbols, &use_def.type_def_loc.unwrap());
+    assert!(loc.uri.path().ends_with("M2.move"));
+    assert_eq!(loc.range.start, Position::new(2, 11));
+}
+
+#[test]
+/// Tests if a multi-line function body produces a single folding range spanning its braces.
+fn folding_ranges_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let ranges = symbols.file_folding_ranges.get(&cpath).unwrap();
+    let lines = |range: &FoldingRange| (range.start_line, range.end_line);
+
+    // the module itself
+    assert!(ranges.iter().map(lines).any(|r| r == (0, 135)));
+    // the body of the cp function
+    let cp_ranges: Vec<_> = ranges
+        .iter()
+        .map(lines)
+        .filter(|(start, _)| *start == 14)
+        .collect();
+    assert_eq!(cp_ranges, vec![(14, 17)]);
+}
+
+#[test]
+/// Tests if renaming a local variable edits its declaration and all its uses, and if renames
+/// colliding with other names in scope or targeting dependencies are rejected.
+fn rename_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+    let files = VirtualFileSystem::default();
+
     let mut fpath = path.clone();
-    fpath.push("sources/M4.move");
+    fpath.push("sources/M1.move");
     let cpath = dunce::canonicalize(&fpath).unwrap();
 
     let mod_symbols = symbols.file_use_defs.get(&cpath).unwrap();
+    // use of a local variable (mut function)
+    let local_use = mod_symbols
+        .get(58)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.col_start == 8)
+        .unwrap();
 
-    // param name in RHS (if_cond function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        1,
-        4,
-        18,
-        2,
-        16,
-        "M4.move",
-        "u64",
-        None,
+    let edit = rename_edits(&symbols, &files, &local_use.def_loc, "new_tmp").unwrap();
+    let changes = edit.changes.unwrap();
+    assert_eq!(changes.len(), 1);
+    let mut edits = changes.into_values().next().unwrap();
+    edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+    let edit = |line, col_start, col_end| TextEdit {
+        range: Range {
+            start: Position {
+                line,
+                character: col_start,
+            },
+            end: Position {
+                line,
+                character: col_end,
+            },
+        },
+        new_text: "new_tmp".to_string(),
+    };
+    assert_eq!(
+        edits,
+        vec![edit(55, 12, 15), edit(56, 21, 24), edit(58, 8, 11)]
     );
-    // param name in RHS (if_cond function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        1,
-        6,
-        22,
-        4,
-        12,
-        "M4.move",
-        "u64",
-        None,
+
+    // `r` is another local in the same function
+    assert!(rename_edits(&symbols, &files, &local_use.def_loc, "r").is_err());
+    // keywords are not valid identifiers
+    assert!(rename_edits(&symbols, &files, &local_use.def_loc, "let").is_err());
+
+    // names are read from the editor's buffers, which may differ from the files on disk
+    assert!(rename_edits(&symbols, &files, &local_use.def_loc, "x").is_ok());
+    let mut buffers = VirtualFileSystem::default();
+    let buffer = std::fs::read_to_string(&cpath)
+        .unwrap()
+        .replace("let r = &mut tmp;", "let x = &mut tmp;")
+        .replace("*r = SOME_CONST;", "*x = SOME_CONST;");
+    buffers.update(cpath.clone(), &buffer);
+    assert!(rename_edits(&symbols, &buffers, &local_use.def_loc, "x").is_err());
+
+    // functions from MoveStdlib cannot be renamed
+    let mut fpath = path.clone();
+    fpath.push("sources/M12.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let dep_use = symbols
+        .file_use_defs
+        .get(&cpath)
+        .unwrap()
+        .get(4)
+        .unwrap()
+        .into_iter()
+        .find(|u| u.col_start == 16)
+        .unwrap();
+    assert!(rename_edits(&symbols, &files, &dep_use.def_loc, "new_fun").is_err());
+}
+
+#[test]
+/// Tests if signature help within the arguments of a call reports the called function's parameters
+/// and the parameter of the argument at the cursor.
+fn signature_help_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // M2::multi_arg(SOME_CONST, SOME_CONST) in the multi_arg_call function
+    let help = |character| {
+        signature_help(&symbols, &cpath, &buffer, &Position::new(40, character)).unwrap()
+    };
+
+    // after the first comma
+    let after_comma = help(34);
+    assert_eq!(after_comma.active_parameter, Some(1));
+    let signature = &after_comma.signatures[0];
+    assert_eq!(
+        signature.label,
+        "fun Symbols::M2::multi_arg(p1: u64, p2: u64): u64"
     );
-    // var in if's true branch (if_cond function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        7,
-        12,
-        4,
-        12,
-        "M4.move",
-        "u64",
-        None,
+    assert_eq!(
+        signature.parameters,
+        Some(vec![
+            ParameterInformation {
+                label: ParameterLabel::Simple("p1: u64".to_string()),
+                documentation: None,
+            },
+            ParameterInformation {
+                label: ParameterLabel::Simple("p2: u64".to_string()),
+                documentation: None,
+            },
+        ])
     );
-    // redefined var in if's false branch (if_cond function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        10,
-        12,
-        9,
-        16,
-        "M4.move",
-        "u64",
-        None,
+
+    // right after the opening parenthesis
+    assert_eq!(help(22).active_parameter, Some(0));
+
+    // after a trailing comma following the last argument
+    let trailing_comma = buffer.replace(
+        "M2::multi_arg(SOME_CONST, SOME_CONST)",
+        "M2::multi_arg(SOME_CONST, SOME_CONST, )",
     );
-    // var name in while loop condition (while_loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        20,
-        15,
-        18,
-        12,
-        "M4.move",
-        "u64",
-        None,
+    let after_trailing_comma =
+        signature_help(&symbols, &cpath, &trailing_comma, &Position::new(40, 46)).unwrap();
+    assert_eq!(after_trailing_comma.active_parameter, Some(1));
+
+    // not within a call
+    assert!(signature_help(&symbols, &cpath, &buffer, &Position::new(39, 4)).is_none());
+
+    // zero() in the zero_call function, which has no parameters to activate
+    let mut fpath = path.clone();
+    fpath.push("sources/M13.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+    let no_args = signature_help(&symbols, &cpath, &buffer, &Position::new(17, 13)).unwrap();
+    assert_eq!(no_args.active_parameter, None);
+    let signature = &no_args.signatures[0];
+    assert_eq!(signature.label, "fun Symbols::M13::zero(): u64");
+    assert_eq!(signature.parameters, Some(vec![]));
+}
+
+#[test]
+/// Tests if identifiers are classified as semantic tokens according to what they refer to.
+fn semantic_tokens_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // decode the token positions
+    let mut tokens = BTreeMap::new();
+    let mut pos = (0, 0);
+    for token in semantic_tokens(&symbols, &cpath, &buffer) {
+        pos = if token.delta_line == 0 {
+            (pos.0, pos.1 + token.delta_start)
+        } else {
+            (pos.0 + token.delta_line, token.delta_start)
+        };
+        tokens.insert(pos, (token.token_type, token.token_modifiers_bitset));
+    }
+
+    // fun unpack(s: SomeStruct): u64 {
+    let function = tokens.get(&(9, 8)).unwrap();
+    let variable = tokens.get(&(9, 15)).unwrap();
+    let structure = tokens.get(&(9, 18)).unwrap();
+    // const SOME_CONST: u64 = 42;
+    let constant = tokens.get(&(6, 10)).unwrap();
+
+    assert_eq!(
+        SEMANTIC_TOKEN_TYPES[function.0 as usize],
+        SemanticTokenType::FUNCTION
     );
-    // var name in while loop's inner block (while_loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        1,
-        23,
-        26,
-        18,
-        12,
-        "M4.move",
-        "u64",
-        None,
+    assert_eq!(
+        SEMANTIC_TOKEN_TYPES[structure.0 as usize],
+        SemanticTokenType::STRUCT
     );
-    // redefined var name in while loop's inner block (while_loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        1,
-        24,
-        23,
-        23,
-        20,
-        "M4.move",
-        "u64",
-        None,
+    assert_ne!(function.0, structure.0);
+    assert_eq!(
+        SEMANTIC_TOKEN_TYPES[variable.0 as usize],
+        SemanticTokenType::VARIABLE
     );
-    // var name in while loop's main block (while_loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        26,
-        12,
-        18,
-        12,
-        "M4.move",
-        "u64",
-        None,
+    assert_eq!(variable.1, 0);
+    assert_eq!(constant.0, variable.0);
+    assert_ne!(constant.1, 0);
+}
+
+#[test]
+/// Tests if inlay hints show the types of unannotated `let` bindings and, if enabled, the parameter
+/// names of call arguments.
+fn inlay_hints_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    let range = Range {
+        start: Position::new(0, 0),
+        end: Position::new(136, 0),
+    };
+    let hints = inlay_hints(&symbols, &cpath, &buffer, &range, true);
+
+    // let tmp = 7;
+    assert!(hints.contains(&InlayHint {
+        position: Position::new(55, 15),
+        label: ": u64".to_string(),
+        kind: INLAY_HINT_KIND_TYPE,
+        padding_right: false,
+    }));
+    // no hint for the (annotated) parameter of fun cp(value: u64): u64
+    assert!(hints.iter().all(|hint| hint.position.line != 13));
+    // M2::multi_arg(SOME_CONST, SOME_CONST)
+    let call_hints: Vec<_> = hints
+        .iter()
+        .filter(|hint| hint.position.line == 40)
+        .map(|hint| (hint.position.character, hint.label.as_str(), hint.kind))
+        .collect();
+    assert_eq!(
+        call_hints,
+        vec![
+            (22, "p1:", INLAY_HINT_KIND_PARAMETER),
+            (34, "p2:", INLAY_HINT_KIND_PARAMETER)
+        ]
     );
-    // redefined var name in while loop's inner block (loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        1,
-        40,
-        23,
-        39,
-        20,
-        "M4.move",
-        "u64",
-        None,
+
+    // parameter name hints are disabled
+    let hints = inlay_hints(&symbols, &cpath, &buffer, &range, false);
+    assert!(hints
+        .iter()
+        .all(|hint| hint.kind != INLAY_HINT_KIND_PARAMETER));
+
+    // the line of `let tmp = 7;` shortened since the symbols were computed
+    let mut lines: Vec<_> = buffer.lines().collect();
+    lines[55] = "    let";
+    let stale = lines.join("\n");
+    let hints = inlay_hints(&symbols, &cpath, &stale, &range, true);
+    assert!(hints.iter().all(|hint| hint.position.line != 55));
+
+    // a range ending before it starts
+    let reversed = Range {
+        start: Position::new(136, 0),
+        end: Position::new(0, 0),
+    };
+    assert!(inlay_hints(&symbols, &cpath, &buffer, &reversed, true).is_empty());
+}
+
+#[test]
+/// Tests if a quick fix imports a function used without being imported, unless it already is or
+/// the identifier resolves to another definition.
+fn import_actions_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M13.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let uri = Url::from_file_path(&cpath).unwrap();
+    let contents = std::fs::read_to_string(&cpath).unwrap();
+
+    // a call added to the end of the module (past the symbolicated contents)
+    let buffer = contents.replace(
+        "        zero()\n    }\n}\n",
+        "        zero()\n    }\n\n    fun f(): u64 {\n        multi_arg(Symbols::M1::cp(1), 2)\n    }\n}\n",
     );
-    // var name in loop's main block (loop function)
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        43,
-        16,
-        34,
-        12,
-        "M4.move",
-        "u64",
-        None,
+    let actions = import_actions(&symbols, &uri, &buffer, &Position::new(21, 10));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].title, "Import `Symbols::M2::multi_arg`");
+    let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+    assert_eq!(
+        changes.get(&uri).unwrap(),
+        &vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 0)),
+            "    use Symbols::M2::multi_arg;\n".to_string(),
+        )]
     );
-    // const in a different module in the same file
-    assert_use_def(
-        mod_symbols,
-        &symbols.file_name_mapping,
-        0,
-        55,
-        10,
-        55,
-        10,
-        "M4.move",
-        "u64",
-        None,
+
+    // qualified names are not imported
+    assert!(import_actions(&symbols, &uri, &buffer, &Position::new(21, 32)).is_empty());
+
+    // already imported
+    let imported = buffer.replace(
+        "module Symbols::M13 {\n",
+        "module Symbols::M13 {\n    use Symbols::M2::{Self, multi_arg};\n",
+    );
+    assert!(import_actions(&symbols, &uri, &imported, &Position::new(22, 10)).is_empty());
+
+    // `len` is a local variable rather than the function declared in M12
+    let mut fpath = path.clone();
+    fpath.push("sources/M14.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let uri = Url::from_file_path(&cpath).unwrap();
+    let contents = std::fs::read_to_string(&cpath).unwrap();
+    // let len = v;
+    assert!(import_actions(&symbols, &uri, &contents, &Position::new(2, 13)).is_empty());
+    // len
+    assert!(import_actions(&symbols, &uri, &contents, &Position::new(3, 9)).is_empty());
+}
+
+#[test]
+/// Tests if the definitions of several identifiers in a file are found at once.
+fn def_locations_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let locations = symbols.def_locations(
+        &cpath,
+        &[
+            // SomeStruct in fun unpack(s: SomeStruct): u64 {
+            (9, 20),
+            // M2::multi_arg(SOME_CONST, SOME_CONST)
+            (40, 12),
+            // not an identifier
+            (1, 0),
+            // value in let ret = value;
+            (15, 18),
+        ],
     );
+    let locations: Vec<_> = locations
+        .iter()
+        .map(|loc| {
+            loc.as_ref().map(|loc| {
+                let file = loc.uri.path_segments().unwrap().last().unwrap().to_string();
+                assert_eq!(loc.range.start, loc.range.end);
+                (file, loc.range.start.line, loc.range.start.character)
+            })
+        })
+        .collect();
+    assert_eq!(
+        locations,
+        vec![
+            Some(("M1.move".to_string(), 2, 11)),
+            Some(("M2.move".to_string(), 10, 15)),
+            None,
+            Some(("M1.move".to_string(), 13, 11)),
+        ]
+    );
+}
+
+#[test]
+/// Tests if the implementation of a defined function is its body, and if native functions have no
+/// implementation.
+fn implementation_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    // call of a function defined in M2
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let (name, location) = implementation(&symbols, &cpath, &Position::new(40, 14)).unwrap();
+    assert_eq!(name.as_str(), "multi_arg");
+    let location = location.unwrap();
+    assert!(location.uri.path().ends_with("M2.move"));
+    assert_eq!(location.range.start, Position::new(10, 48));
+
+    // call of a native function from MoveStdlib
+    let mut fpath = path.clone();
+    fpath.push("sources/M12.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let (name, location) = implementation(&symbols, &cpath, &Position::new(4, 17)).unwrap();
+    assert_eq!(name.as_str(), "length");
+    assert!(location.is_none());
+
+    // not a function
+    assert!(implementation(&symbols, &cpath, &Position::new(3, 12)).is_none());
 }
@@ -0,0 +1,12 @@
+module Symbols::M10 {
+
+    #[test_only]
+    fun helper(x: u64): u64 {
+        x + 1
+    }
+
+    #[test]
+    fun helper_test() {
+        assert!(helper(1) == 2, 0);
+    }
+}
@@ -0,0 +1,7 @@
+module Symbols::M11 {
+
+    struct Table<K: copy + drop, V: store> has store {
+        keys: vector<K>,
+        values: vector<V>,
+    }
+}
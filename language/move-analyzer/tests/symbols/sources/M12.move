@@ -0,0 +1,11 @@
+module Symbols::M12 {
+    use std::vector;
+
+    fun len(v: &vector<u64>): u64 {
+        vector::length(v)
+    }
+
+    fun other(s: Symbols::M2::SomeOtherStruct): Symbols::M2::SomeOtherStruct {
+        s
+    }
+}
@@ -0,0 +1,20 @@
+module Symbols::M13 {
+    fun scopes(p: u64): u64 {
+        let a = p;
+        {
+            let b = a;
+            let a = b + 1;
+            a
+        };
+        let c = a;
+        c
+    }
+
+    fun zero(): u64 {
+        42
+    }
+
+    fun zero_call(): u64 {
+        zero()
+    }
+}
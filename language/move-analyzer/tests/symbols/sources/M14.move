@@ -0,0 +1,6 @@
+module Symbols::M14 {
+    fun local_len(v: u64): u64 {
+        let len = v;
+        len
+    }
+}
@@ -0,0 +1,20 @@
+module Symbols::M8 {
+
+    const EINVALID_ARG: u64 = 1;
+
+    const EOTHER: u64 = 2;
+
+    fun abort_with_const() {
+        abort EINVALID_ARG
+    }
+
+    fun assert_with_const(p: bool) {
+        assert!(p, EOTHER);
+    }
+
+    use Symbols::M2::{SomeOtherStruct as Other, some_other_struct};
+
+    fun aliased_import(): Other {
+        some_other_struct(42)
+    }
+}
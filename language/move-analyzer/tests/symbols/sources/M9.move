@@ -0,0 +1,31 @@
+module Symbols::M9 {
+
+    spec schema AbortsIfZero {
+        x: u64;
+        aborts_if x == 0;
+    }
+
+    fun one(x: u64): u64 {
+        10 / x
+    }
+
+    spec one {
+        include AbortsIfZero;
+    }
+
+    fun two(x: u64): u64 {
+        20 / x
+    }
+
+    spec two {
+        include AbortsIfZero;
+    }
+
+    fun three(x: u64, y: u64): u64 {
+        y / x
+    }
+
+    spec three {
+        include y > 0 ==> AbortsIfZero;
+    }
+}
@@ -335,11 +335,20 @@ fn duplicate_module(
     context: &mut Context,
     module_map: &UniqueMap<ModuleIdent, E::ModuleDefinition>,
     mident: ModuleIdent,
+    package_name: Option<Symbol>,
     old_loc: Loc,
 ) {
     let old_mident = module_map.get_key(&mident).unwrap();
-    let dup_msg = format!("Duplicate definition for module '{}'", mident);
-    let prev_msg = format!("Module previously defined here, with '{}'", old_mident);
+    let mut dup_msg = format!("Duplicate definition for module '{}'", mident);
+    let mut prev_msg = format!("Module previously defined here, with '{}'", old_mident);
+    // name the packages involved if the module is defined by two different packages
+    let old_package_name = module_map.get(&mident).unwrap().package_name;
+    if let (Some(pkg), Some(old_pkg)) = (package_name, old_package_name) {
+        if pkg != old_pkg {
+            dup_msg = format!("{} in package '{}'", dup_msg, pkg);
+            prev_msg = format!("{} in package '{}'", prev_msg, old_pkg);
+        }
+    }
     context.env.add_diag(diag!(
         Declarations::DuplicateItem,
         (mident.loc, dup_msg),
@@ -357,7 +366,7 @@ fn module(
     assert!(context.address.is_none());
     let (mident, mod_) = module_(context, package_name, module_address, module_def);
     if let Err((mident, old_loc)) = module_map.add(mident, mod_) {
-        duplicate_module(context, module_map, mident, old_loc)
+        duplicate_module(context, module_map, mident, package_name, old_loc)
     }
     context.address = None
 }
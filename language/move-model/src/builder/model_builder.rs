@@ -19,7 +19,7 @@ use move_compiler::{expansion::ast as EA, parser::ast as PA, shared::NumericalAd
 use crate::{
     ast::{Attribute, ModuleName, Operation, QualifiedSymbol, Spec, Value},
     builder::spec_builtins,
-    intrinsics::IntrinsicDecl,
+    intrinsics::{resolve_intrinsic_declaration, PendingIntrinsicDecl},
     model::{
         FunId, FunctionVisibility, GlobalEnv, Loc, ModuleId, QualifiedId, SpecFunId, SpecVarId,
         StructId,
@@ -58,8 +58,8 @@ pub(crate) struct ModelBuilder<'env> {
     pub const_table: BTreeMap<QualifiedSymbol, ConstEntry>,
     /// A call graph mapping callers to callees that are Move functions.
     pub move_fun_call_graph: BTreeMap<QualifiedId<SpecFunId>, BTreeSet<QualifiedId<SpecFunId>>>,
-    /// A list of intrinsic declarations
-    pub intrinsics: Vec<IntrinsicDecl>,
+    /// A list of intrinsic declarations, pending the resolution of their function mappings
+    pub intrinsics: Vec<PendingIntrinsicDecl>,
 }
 
 /// A declaration of a specification function or operator in the builders state.
@@ -418,9 +418,10 @@ impl<'env> ModelBuilder<'env> {
 
     /// Pass model-level information to the global env
     pub fn populate_env(&mut self) {
-        // register all intrinsic declarations
-        for decl in &self.intrinsics {
-            self.env.intrinsics.add_decl(decl);
+        // resolve and register all intrinsic declarations
+        for pending in std::mem::take(&mut self.intrinsics) {
+            let decl = resolve_intrinsic_declaration(self, pending);
+            self.env.intrinsics.add_decl(&decl);
         }
     }
 }
@@ -5,18 +5,35 @@
 use std::{collections::BTreeMap, ops::Deref};
 
 use num::BigUint;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    ast::{Operation, PropertyBag, PropertyValue, QualifiedSymbol},
-    builder::module_builder::SpecBlockContext,
+    ast::{ModuleName, Operation, PropertyBag, PropertyValue, QualifiedSymbol},
+    builder::{model_builder::ModelBuilder, module_builder::SpecBlockContext},
     model::{IntrinsicId, QualifiedId, SpecFunId},
-    pragmas::{INTRINSIC_PRAGMA, INTRINSIC_TYPE_MAP, INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS},
+    pragmas::{
+        INTRINSIC_FUN_MAP_ADD_NO_OVERRIDE, INTRINSIC_FUN_MAP_ADD_OVERRIDE_IF_EXISTS,
+        INTRINSIC_FUN_MAP_BORROW, INTRINSIC_FUN_MAP_BORROW_MUT, INTRINSIC_FUN_MAP_DEL_MUST_EXIST,
+        INTRINSIC_FUN_MAP_DEL_RETURN_KEY, INTRINSIC_FUN_MAP_DESTROY_EMPTY,
+        INTRINSIC_FUN_MAP_HAS_KEY, INTRINSIC_FUN_MAP_IS_EMPTY, INTRINSIC_FUN_MAP_LEN,
+        INTRINSIC_FUN_MAP_NEW, INTRINSIC_FUN_MAP_SPEC_DEL, INTRINSIC_FUN_MAP_SPEC_GET,
+        INTRINSIC_FUN_MAP_SPEC_HAS_KEY, INTRINSIC_FUN_MAP_SPEC_IS_EMPTY,
+        INTRINSIC_FUN_MAP_SPEC_LEN, INTRINSIC_FUN_MAP_SPEC_NEW, INTRINSIC_FUN_MAP_SPEC_SET,
+        INTRINSIC_FUN_MODULE_PROP, INTRINSIC_FUN_VECTOR_BORROW, INTRINSIC_FUN_VECTOR_BORROW_MUT,
+        INTRINSIC_FUN_VECTOR_DESTROY_EMPTY, INTRINSIC_FUN_VECTOR_IS_EMPTY,
+        INTRINSIC_FUN_VECTOR_LEN, INTRINSIC_FUN_VECTOR_NEW, INTRINSIC_FUN_VECTOR_POP_BACK,
+        INTRINSIC_FUN_VECTOR_PUSH_BACK, INTRINSIC_FUN_VECTOR_SPEC_GET,
+        INTRINSIC_FUN_VECTOR_SPEC_LEN, INTRINSIC_FUN_VECTOR_SPEC_NEW, INTRINSIC_PRAGMA,
+        INTRINSIC_TYPE_MAP, INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS, INTRINSIC_TYPE_VECTOR,
+        INTRINSIC_TYPE_VECTOR_ASSOC_FUNCTIONS,
+    },
     symbol::{Symbol, SymbolPool},
+    ty::{PrimitiveType, Type},
     FunId, GlobalEnv, Loc, ModuleBuilder, StructId,
 };
 
 /// An information pack that holds the intrinsic declaration
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntrinsicDecl {
     move_type: QualifiedId<StructId>,
     intrinsic_type: Symbol,
@@ -62,6 +79,22 @@ impl IntrinsicDecl {
     }
 }
 
+/// An intrinsic declaration whose function mappings are yet to be resolved.  The resolution is
+/// deferred until all modules are built, such that the mappings can refer to functions declared in
+/// modules built after the one declaring the intrinsic type.
+#[derive(Debug)]
+pub(crate) struct PendingIntrinsicDecl {
+    loc: Loc,
+    /// The module declaring the intrinsic type
+    module_name: ModuleName,
+    /// The simple name of the intrinsic type
+    type_name: Symbol,
+    associated_funs: &'static BTreeMap<&'static str, bool>,
+    /// The function mappings, and the `intrinsic_fun_module` if any, taken from the pragma
+    mappings: PropertyBag,
+    decl: IntrinsicDecl,
+}
+
 pub(crate) fn process_intrinsic_declaration(
     builder: &mut ModuleBuilder,
     loc: &Loc,
@@ -104,6 +137,7 @@ pub(crate) fn process_intrinsic_declaration(
     // obtain the associated functions map
     let associated_funs = match target.as_str() {
         INTRINSIC_TYPE_MAP => INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS.deref(),
+        INTRINSIC_TYPE_VECTOR => INTRINSIC_TYPE_VECTOR_ASSOC_FUNCTIONS.deref(),
         _ => {
             builder
                 .parent
@@ -116,7 +150,7 @@ pub(crate) fn process_intrinsic_declaration(
     let type_entry = builder.parent.struct_table.get(&type_qsym).expect("struct");
     let move_type = type_entry.module_id.qualified(type_entry.struct_id);
 
-    let mut decl = IntrinsicDecl {
+    let decl = IntrinsicDecl {
         move_type,
         intrinsic_type: symbol_pool.make(target.as_str()),
         intrinsic_to_move_fun: BTreeMap::new(),
@@ -125,62 +159,133 @@ pub(crate) fn process_intrinsic_declaration(
         spec_fun_to_intrinsic: BTreeMap::new(),
     };
 
-    // construct the pack
-    populate_intrinsic_decl(builder, loc, associated_funs, props, &mut decl);
+    // take the function mappings out of the pragma, they are resolved once all modules are built
+    let mut mappings = PropertyBag::default();
+    for name in associated_funs
+        .keys()
+        .copied()
+        .chain([INTRINSIC_FUN_MODULE_PROP])
+    {
+        let sym = symbol_pool.make(name);
+        if let Some(val) = props.remove(&sym) {
+            mappings.insert(sym, val);
+        }
+    }
 
     // add the decl back
-    builder.parent.intrinsics.push(decl);
+    builder.parent.intrinsics.push(PendingIntrinsicDecl {
+        loc: loc.clone(),
+        module_name: builder.module_name.clone(),
+        type_name: type_qsym.symbol,
+        associated_funs,
+        mappings,
+        decl,
+    });
 }
 
+/// Resolves the function mappings of an intrinsic declaration, once all modules are built
+pub(crate) fn resolve_intrinsic_declaration(
+    builder: &ModelBuilder,
+    pending: PendingIntrinsicDecl,
+) -> IntrinsicDecl {
+    let PendingIntrinsicDecl {
+        loc,
+        module_name,
+        type_name,
+        associated_funs,
+        mut mappings,
+        mut decl,
+    } = pending;
+
+    // obtain the additional module the mappings may refer to, if any
+    let symbol_pool = builder.env.symbol_pool();
+    let fun_module = match mappings.remove(&symbol_pool.make(INTRINSIC_FUN_MODULE_PROP)) {
+        None => None,
+        Some(PropertyValue::Symbol(sym)) => Some(sym),
+        Some(_) => {
+            builder.error(
+                &loc,
+                &format!("expect a module name for `{}`", INTRINSIC_FUN_MODULE_PROP),
+            );
+            None
+        }
+    };
+
+    // construct the pack
+    populate_intrinsic_decl(
+        builder,
+        &loc,
+        &module_name,
+        type_name,
+        fun_module,
+        associated_funs,
+        &mut mappings,
+        &mut decl,
+    );
+    decl
+}
+
+#[allow(clippy::too_many_arguments)]
 fn populate_intrinsic_decl(
-    builder: &mut ModuleBuilder,
+    builder: &ModelBuilder,
     loc: &Loc,
+    module_name: &ModuleName,
+    type_name: Symbol,
+    fun_module: Option<Symbol>,
     associated_funs: &BTreeMap<&str, bool>,
     props: &mut PropertyBag,
     decl: &mut IntrinsicDecl,
 ) {
-    let symbol_pool = builder.symbol_pool();
+    let symbol_pool = builder.env.symbol_pool();
     for (&name, &is_move_fun) in associated_funs {
         let key_sym = symbol_pool.make(name);
 
         // look-up the target of the declaration, if present
-        let target_sym = match props.remove(&key_sym) {
+        let qualified_sym = match props.remove(&key_sym) {
             None => {
                 continue;
             }
             Some(PropertyValue::Value(_)) => {
-                builder.parent.error(
+                builder.error(
                     loc,
                     &format!("invalid intrinsic function mapping: {}", name),
                 );
                 continue;
             }
-            Some(PropertyValue::Symbol(val_sym)) => val_sym,
+            Some(PropertyValue::Symbol(val_sym)) => QualifiedSymbol {
+                module_name: module_name.clone(),
+                symbol: val_sym,
+            },
             Some(PropertyValue::QualifiedSymbol(qual_sym)) => {
-                if qual_sym.module_name != builder.module_name {
-                    builder.parent.error(
+                if &qual_sym.module_name != module_name
+                    && !is_intrinsic_fun_module(
+                        builder,
+                        module_name,
+                        fun_module,
+                        &qual_sym.module_name,
+                    )
+                {
+                    builder.error(
                         loc,
                         &format!(
                             "an intrinsic function mapping can only refer to functions \
-                            declared in the same module while `{}` is not",
+                            declared in the same module, a friend module, or the \
+                            `{}` while `{}` is not",
+                            INTRINSIC_FUN_MODULE_PROP,
                             qual_sym.display(symbol_pool)
                         ),
                     );
                     continue;
                 }
-                qual_sym.symbol
+                qual_sym
             }
         };
-        let qualified_sym = QualifiedSymbol {
-            module_name: builder.module_name.clone(),
-            symbol: target_sym,
-        };
 
         // check presence
         if is_move_fun {
-            match builder.parent.fun_table.get(&qualified_sym) {
+            match builder.fun_table.get(&qualified_sym) {
                 None => {
-                    builder.parent.error(
+                    builder.error(
                         loc,
                         &format!(
                             "unable to find move function for intrinsic mapping: {}",
@@ -190,13 +295,10 @@ fn populate_intrinsic_decl(
                     continue;
                 }
                 Some(entry) => {
-                    // TODO: in theory, we should also do some type checking on the function
-                    // signature. This is implicitly done by Boogie right now, but we may want to
-                    // make it more explicit and do the checking ourselves.
                     let qid = entry.module_id.qualified(entry.fun_id);
                     decl.intrinsic_to_move_fun.insert(key_sym, qid);
                     if decl.move_fun_to_intrinsic.insert(qid, key_sym).is_some() {
-                        builder.parent.error(
+                        builder.error(
                             loc,
                             &format!(
                                 "duplicated intrinsic mapping for move function: {}",
@@ -205,12 +307,35 @@ fn populate_intrinsic_decl(
                         );
                         continue;
                     }
+
+                    let param_types: Vec<_> = entry.params.iter().map(|(_, ty)| ty).collect();
+                    if !is_intrinsic_signature(
+                        name,
+                        decl.move_type,
+                        &param_types,
+                        &entry.result_type,
+                    ) {
+                        builder.error(
+                            loc,
+                            &format!(
+                                "signature of move function {} does not match intrinsic {}, \
+                                expected `{}`",
+                                qualified_sym.display(symbol_pool),
+                                name,
+                                display_intrinsic_signature(
+                                    name,
+                                    &type_name.display(symbol_pool).to_string()
+                                ),
+                            ),
+                        );
+                        continue;
+                    }
                 }
             }
         } else {
-            match builder.parent.spec_fun_table.get(&qualified_sym) {
+            match builder.spec_fun_table.get(&qualified_sym) {
                 None => {
-                    builder.parent.error(
+                    builder.error(
                         loc,
                         &format!(
                             "unable to find spec function for intrinsic mapping: {}",
@@ -221,7 +346,7 @@ fn populate_intrinsic_decl(
                 }
                 Some(entries) => {
                     if entries.len() != 1 {
-                        builder.parent.error(
+                        builder.error(
                             loc,
                             &format!(
                                 "unable to find a unique spec function for intrinsic mapping: {}",
@@ -231,15 +356,11 @@ fn populate_intrinsic_decl(
                         continue;
                     }
                     let entry = &entries[0];
-
-                    // TODO: in theory, we should also do some type checking on the function
-                    // signature. This is implicitly done by Boogie right now, but we may want to
-                    // make it more explicit and do the checking ourselves.
                     if let Operation::Function(mid, fid, ..) = &entry.oper {
                         let qid = mid.qualified(*fid);
                         decl.intrinsic_to_spec_fun.insert(key_sym, qid);
                         if decl.spec_fun_to_intrinsic.insert(qid, key_sym).is_some() {
-                            builder.parent.error(
+                            builder.error(
                                 loc,
                                 &format!(
                                     "duplicated intrinsic mapping for spec function: {}",
@@ -248,6 +369,29 @@ fn populate_intrinsic_decl(
                             );
                             continue;
                         }
+
+                        let param_types: Vec<_> = entry.arg_types.iter().collect();
+                        if !is_intrinsic_signature(
+                            name,
+                            decl.move_type,
+                            &param_types,
+                            &entry.result_type,
+                        ) {
+                            builder.error(
+                                loc,
+                                &format!(
+                                    "signature of spec function {} does not match intrinsic {}, \
+                                    expected `{}`",
+                                    qualified_sym.display(symbol_pool),
+                                    name,
+                                    display_intrinsic_signature(
+                                        name,
+                                        &type_name.display(symbol_pool).to_string()
+                                    ),
+                                ),
+                            );
+                            continue;
+                        }
                     }
                 }
             }
@@ -255,8 +399,154 @@ fn populate_intrinsic_decl(
     }
 }
 
+/// Whether the intrinsic functions of a type declared in `module_name` may be mapped to functions
+/// declared in `target`, i.e., whether `target` is declared as a friend of that module, or is the
+/// `intrinsic_fun_module` of the declaration (which names a module at the same address)
+fn is_intrinsic_fun_module(
+    builder: &ModelBuilder,
+    module_name: &ModuleName,
+    fun_module: Option<Symbol>,
+    target: &ModuleName,
+) -> bool {
+    if let Some(fun_module) = fun_module {
+        if &ModuleName::new(module_name.addr().clone(), fun_module) == target {
+            return true;
+        }
+    }
+    builder
+        .env
+        .find_module(module_name)
+        .map_or(false, |module_env| {
+            module_env
+                .get_friends()
+                .iter()
+                .any(|friend| &builder.env.to_module_name(friend) == target)
+        })
+}
+
+/// The shape of a parameter or result type in the signature expected of an intrinsic function
+#[derive(Clone, Copy)]
+enum TypeShape {
+    /// The intrinsic type itself
+    Intrinsic,
+    /// A reference to the intrinsic type, mutable if the flag is set
+    IntrinsicRef(bool),
+    /// Any non-reference type, e.g., a key, a value, or an element
+    Elem,
+    /// A reference to a key, a value, or an element, mutable if the flag is set
+    ElemRef(bool),
+    U64,
+    Num,
+    Bool,
+}
+
+impl TypeShape {
+    fn matches(self, ty: &Type, move_type: QualifiedId<StructId>) -> bool {
+        match (self, ty) {
+            (TypeShape::Intrinsic, Type::Struct(mid, sid, _)) => mid.qualified(*sid) == move_type,
+            (TypeShape::IntrinsicRef(is_mut), Type::Reference(ref_is_mut, inner)) => {
+                is_mut == *ref_is_mut && TypeShape::Intrinsic.matches(inner, move_type)
+            }
+            (TypeShape::Elem, _) => !ty.is_reference() && !matches!(ty, Type::Tuple(_)),
+            (TypeShape::ElemRef(is_mut), Type::Reference(ref_is_mut, _)) => is_mut == *ref_is_mut,
+            (TypeShape::U64, Type::Primitive(PrimitiveType::U64)) => true,
+            (TypeShape::Num, _) => ty.is_number(),
+            (TypeShape::Bool, Type::Primitive(PrimitiveType::Bool)) => true,
+            _ => false,
+        }
+    }
+
+    fn display(self, type_name: &str) -> String {
+        match self {
+            TypeShape::Intrinsic => type_name.to_string(),
+            TypeShape::IntrinsicRef(is_mut) => {
+                format!("&{}{}", if is_mut { "mut " } else { "" }, type_name)
+            }
+            TypeShape::Elem => "_".to_string(),
+            TypeShape::ElemRef(is_mut) => format!("&{}_", if is_mut { "mut " } else { "" }),
+            TypeShape::U64 => "u64".to_string(),
+            TypeShape::Num => "num".to_string(),
+            TypeShape::Bool => "bool".to_string(),
+        }
+    }
+}
+
+/// The expected parameter and result shapes of each intrinsic function, following the signatures
+/// documented in the `pragmas` module
+fn intrinsic_fun_shapes(name: &str) -> (&'static [TypeShape], &'static [TypeShape]) {
+    use TypeShape::*;
+    match name {
+        INTRINSIC_FUN_MAP_NEW | INTRINSIC_FUN_MAP_SPEC_NEW => (&[], &[Intrinsic]),
+        INTRINSIC_FUN_MAP_SPEC_GET => (&[Intrinsic, Elem], &[Elem]),
+        INTRINSIC_FUN_MAP_SPEC_SET => (&[Intrinsic, Elem, Elem], &[Intrinsic]),
+        INTRINSIC_FUN_MAP_SPEC_DEL => (&[Intrinsic, Elem], &[Intrinsic]),
+        INTRINSIC_FUN_MAP_SPEC_LEN => (&[Intrinsic], &[Num]),
+        INTRINSIC_FUN_MAP_SPEC_IS_EMPTY => (&[Intrinsic], &[Bool]),
+        INTRINSIC_FUN_MAP_SPEC_HAS_KEY => (&[Intrinsic, Elem], &[Bool]),
+        INTRINSIC_FUN_MAP_LEN => (&[IntrinsicRef(false)], &[U64]),
+        INTRINSIC_FUN_MAP_IS_EMPTY => (&[IntrinsicRef(false)], &[Bool]),
+        INTRINSIC_FUN_MAP_HAS_KEY => (&[IntrinsicRef(false), Elem], &[Bool]),
+        INTRINSIC_FUN_MAP_DESTROY_EMPTY => (&[Intrinsic], &[]),
+        INTRINSIC_FUN_MAP_ADD_NO_OVERRIDE | INTRINSIC_FUN_MAP_ADD_OVERRIDE_IF_EXISTS => {
+            (&[IntrinsicRef(true), Elem, Elem], &[])
+        }
+        INTRINSIC_FUN_MAP_DEL_MUST_EXIST => (&[IntrinsicRef(true), Elem], &[Elem]),
+        INTRINSIC_FUN_MAP_DEL_RETURN_KEY => (&[IntrinsicRef(true), Elem], &[Elem, Elem]),
+        INTRINSIC_FUN_MAP_BORROW => (&[IntrinsicRef(false), Elem], &[ElemRef(false)]),
+        INTRINSIC_FUN_MAP_BORROW_MUT => (&[IntrinsicRef(true), Elem], &[ElemRef(true)]),
+        INTRINSIC_FUN_VECTOR_NEW | INTRINSIC_FUN_VECTOR_SPEC_NEW => (&[], &[Intrinsic]),
+        INTRINSIC_FUN_VECTOR_SPEC_GET => (&[Intrinsic, Num], &[Elem]),
+        INTRINSIC_FUN_VECTOR_SPEC_LEN => (&[Intrinsic], &[Num]),
+        INTRINSIC_FUN_VECTOR_LEN => (&[IntrinsicRef(false)], &[U64]),
+        INTRINSIC_FUN_VECTOR_IS_EMPTY => (&[IntrinsicRef(false)], &[Bool]),
+        INTRINSIC_FUN_VECTOR_PUSH_BACK => (&[IntrinsicRef(true), Elem], &[]),
+        INTRINSIC_FUN_VECTOR_POP_BACK => (&[IntrinsicRef(true)], &[Elem]),
+        INTRINSIC_FUN_VECTOR_BORROW => (&[IntrinsicRef(false), U64], &[ElemRef(false)]),
+        INTRINSIC_FUN_VECTOR_BORROW_MUT => (&[IntrinsicRef(true), U64], &[ElemRef(true)]),
+        INTRINSIC_FUN_VECTOR_DESTROY_EMPTY => (&[Intrinsic], &[]),
+        _ => unreachable!("unknown intrinsic function: {}", name),
+    }
+}
+
+/// Check whether the parameter and result types of a function mapped to intrinsic `name` match
+/// the shape expected of that intrinsic
+fn is_intrinsic_signature(
+    name: &str,
+    move_type: QualifiedId<StructId>,
+    param_types: &[&Type],
+    result_type: &Type,
+) -> bool {
+    let result_types = match result_type {
+        Type::Tuple(tys) => tys.as_slice(),
+        ty => std::slice::from_ref(ty),
+    };
+    let (param_shapes, result_shapes) = intrinsic_fun_shapes(name);
+    param_types.len() == param_shapes.len()
+        && result_types.len() == result_shapes.len()
+        && param_shapes
+            .iter()
+            .zip(param_types)
+            .all(|(shape, ty)| shape.matches(ty, move_type))
+        && result_shapes
+            .iter()
+            .zip(result_types)
+            .all(|(shape, ty)| shape.matches(ty, move_type))
+}
+
+/// Render the signature expected of intrinsic `name`, for error messages
+fn display_intrinsic_signature(name: &str, type_name: &str) -> String {
+    let (param_shapes, result_shapes) = intrinsic_fun_shapes(name);
+    let params: Vec<_> = param_shapes.iter().map(|s| s.display(type_name)).collect();
+    let results: Vec<_> = result_shapes.iter().map(|s| s.display(type_name)).collect();
+    match results.len() {
+        0 => format!("fun({})", params.join(", ")),
+        1 => format!("fun({}): {}", params.join(", "), results[0]),
+        _ => format!("fun({}): ({})", params.join(", "), results.join(", ")),
+    }
+}
+
 /// Hosts all intrinsic declarations
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntrinsicsAnnotation {
     /// Intrinsic declarations
     decls: BTreeMap<IntrinsicId, IntrinsicDecl>,
@@ -269,6 +559,18 @@ pub struct IntrinsicsAnnotation {
 }
 
 impl IntrinsicsAnnotation {
+    /// Serialize the annotation (e.g., to cache it across runs).  Symbols and modules are
+    /// serialized by index, so the result is only meaningful for a `GlobalEnv` built from the
+    /// same sources.
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    /// Deserialize an annotation serialized by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        Ok(bcs::from_bytes(bytes)?)
+    }
+
     /// Add a declaration pack into the annotation set
     pub fn add_decl(&mut self, decl: &IntrinsicDecl) {
         let id = IntrinsicId::new(self.decls.len());
@@ -282,6 +584,21 @@ impl IntrinsicsAnnotation {
         self.decls.insert(id, decl.clone());
     }
 
+    /// Iterate over all intrinsic declarations, in the order they were added
+    pub fn decls(&self) -> impl Iterator<Item = &IntrinsicDecl> {
+        self.decls.values()
+    }
+
+    /// Test whether a move function is mapped to an intrinsic function
+    pub fn is_intrinsic_fun(&self, qid: &QualifiedId<FunId>) -> bool {
+        self.intrinsic_move_funs.contains_key(qid)
+    }
+
+    /// Test whether a spec function is mapped to an intrinsic function
+    pub fn is_intrinsic_spec_fun(&self, qid: &QualifiedId<SpecFunId>) -> bool {
+        self.intrinsic_spec_funs.contains_key(qid)
+    }
+
     /// Get the intrinsic decl for struct
     pub fn get_decl_for_struct(&self, qid: &QualifiedId<StructId>) -> Option<&IntrinsicDecl> {
         self.intrinsic_structs
@@ -355,3 +672,120 @@ impl IntrinsicsAnnotation {
             .map_or(false, |sym| sym == &symbol_pool.make(intrinsic_name))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::ModuleId, run_model_builder};
+    use move_compiler::shared::PackagePaths;
+
+    /// A declaration of `Table` in module 0 as a `map` intrinsic, returning it along with its
+    /// mapped move and spec functions.
+    fn table_decl(
+        pool: &SymbolPool,
+    ) -> (IntrinsicDecl, QualifiedId<FunId>, QualifiedId<SpecFunId>) {
+        let module_id = ModuleId::new(0);
+        let move_fun = module_id.qualified(FunId::new(pool.make("borrow")));
+        let spec_fun = module_id.qualified(SpecFunId::new(0));
+
+        let decl = IntrinsicDecl {
+            move_type: module_id.qualified(StructId::new(pool.make("Table"))),
+            intrinsic_type: pool.make("map"),
+            intrinsic_to_move_fun: BTreeMap::from([(pool.make("map_borrow"), move_fun)]),
+            move_fun_to_intrinsic: BTreeMap::from([(move_fun, pool.make("map_borrow"))]),
+            intrinsic_to_spec_fun: BTreeMap::from([(pool.make("map_spec_get"), spec_fun)]),
+            spec_fun_to_intrinsic: BTreeMap::from([(spec_fun, pool.make("map_spec_get"))]),
+        };
+
+        (decl, move_fun, spec_fun)
+    }
+
+    #[test]
+    fn annotation_round_trip() {
+        let pool = SymbolPool::new();
+        let (decl, _, _) = table_decl(&pool);
+
+        let mut annotation = IntrinsicsAnnotation::default();
+        annotation.add_decl(&decl);
+
+        let bytes = annotation.to_bytes().unwrap();
+        assert_eq!(
+            IntrinsicsAnnotation::from_bytes(&bytes).unwrap(),
+            annotation
+        );
+    }
+
+    #[test]
+    fn annotation_queries() {
+        let pool = SymbolPool::new();
+        let (decl, move_fun, spec_fun) = table_decl(&pool);
+
+        let mut annotation = IntrinsicsAnnotation::default();
+        annotation.add_decl(&decl);
+
+        assert_eq!(annotation.decls().collect::<Vec<_>>(), vec![&decl]);
+        assert_eq!(annotation.get_decl_for_struct(&decl.move_type), Some(&decl));
+        assert_eq!(annotation.get_decl_for_move_fun(&move_fun), Some(&decl));
+        assert_eq!(annotation.get_decl_for_spec_fun(&spec_fun), Some(&decl));
+        assert!(annotation.is_intrinsic_fun(&move_fun));
+        assert!(annotation.is_intrinsic_spec_fun(&spec_fun));
+        assert!(annotation.is_intrinsic_of_for_move_fun(&pool, &move_fun, "map_borrow"));
+        assert!(!annotation.is_intrinsic_of_for_move_fun(&pool, &move_fun, "map_len"));
+
+        // ids that are not mapped to intrinsics
+        let module_id = ModuleId::new(1);
+        let other_struct = module_id.qualified(StructId::new(pool.make("Table")));
+        let other_fun = module_id.qualified(FunId::new(pool.make("borrow")));
+        let other_spec_fun = module_id.qualified(SpecFunId::new(0));
+        assert_eq!(annotation.get_decl_for_struct(&other_struct), None);
+        assert_eq!(annotation.get_decl_for_move_fun(&other_fun), None);
+        assert_eq!(annotation.get_decl_for_spec_fun(&other_spec_fun), None);
+        assert!(!annotation.is_intrinsic_fun(&other_fun));
+        assert!(!annotation.is_intrinsic_spec_fun(&other_spec_fun));
+    }
+
+    #[test]
+    fn vector_intrinsic_decl() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/sources/intrinsic_decl_vector_ok.move"
+        );
+        let targets = vec![PackagePaths {
+            name: None,
+            paths: vec![path.to_string()],
+            named_address_map: BTreeMap::<String, _>::new(),
+        }];
+        let env = run_model_builder(targets, vec![]).unwrap();
+        assert!(!env.has_errors());
+
+        let module = env
+            .find_module_by_name(env.symbol_pool().make("V"))
+            .unwrap();
+        let my_vec = module.find_struct(env.symbol_pool().make("MyVec")).unwrap();
+        let decl = env
+            .intrinsics
+            .get_decl_for_struct(&my_vec.get_qualified_id())
+            .unwrap();
+
+        assert_eq!(
+            env.symbol_pool().string(decl.intrinsic_type).as_str(),
+            INTRINSIC_TYPE_VECTOR
+        );
+        assert_eq!(decl.intrinsic_to_move_fun.len(), 4);
+        assert_eq!(decl.intrinsic_to_spec_fun.len(), 2);
+        assert_eq!(
+            decl.get_fun_triple(&env, "vector_len"),
+            Some((BigUint::from(0x42u8), "V".to_string(), "length".to_string()))
+        );
+        assert_eq!(
+            decl.get_fun_triple(&env, "vector_spec_get"),
+            Some((
+                BigUint::from(0x42u8),
+                "V".to_string(),
+                "spec_get".to_string()
+            ))
+        );
+        assert!(decl.lookup_spec_fun(&env, "vector_spec_len").is_some());
+        assert!(decl.get_fun_triple(&env, "vector_pop_back").is_none());
+    }
+}
@@ -181,7 +181,7 @@ pub type MoveIrLoc = move_ir_types::location::Loc;
 pub type RawIndex = u16;
 
 /// Identifier for a module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct ModuleId(RawIndex);
 
 /// Identifier for a named constant, relative to module.
@@ -189,7 +189,7 @@ pub struct ModuleId(RawIndex);
 pub struct NamedConstantId(Symbol);
 
 /// Identifier for a structure/resource, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct StructId(Symbol);
 
 /// Identifier for a field of a structure, relative to struct.
@@ -197,7 +197,7 @@ pub struct StructId(Symbol);
 pub struct FieldId(Symbol);
 
 /// Identifier for a Move function, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct FunId(Symbol);
 
 /// Identifier for a schema.
@@ -205,7 +205,7 @@ pub struct FunId(Symbol);
 pub struct SchemaId(Symbol);
 
 /// Identifier for a specification function, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct SpecFunId(RawIndex);
 
 /// Identifier for a specification variable, relative to module.
@@ -222,11 +222,11 @@ pub struct NodeId(usize);
 pub struct GlobalId(usize);
 
 /// Identifier for an intrinsic declaration, relative globally in `GlobalEnv`.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct IntrinsicId(usize);
 
 /// Some identifier qualified by a module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct QualifiedId<Id> {
     pub module_id: ModuleId,
     pub id: Id,
@@ -80,6 +80,11 @@ pub const DELEGATE_INVARIANTS_TO_CALLER_PRAGMA: &str = "delegate_invariants_to_c
 
 /// # Pragmas for intrinsic table declaration
 
+/// Property naming a module, other than the one declaring the intrinsic type, whose functions the
+/// intrinsic functions may be mapped to, e.g., `pragma intrinsic = map, intrinsic_fun_module = Ops`.
+/// Functions of modules declared as friends of the declaring module can be mapped without it.
+pub const INTRINSIC_FUN_MODULE_PROP: &str = "intrinsic_fun_module";
+
 /// The intrinsic type for `Map<K, V>`
 pub const INTRINSIC_TYPE_MAP: &str = "map";
 
@@ -182,6 +187,72 @@ pub static INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS: Lazy<BTreeMap<&'static str, bool>
         ])
     });
 
+/// The intrinsic type for `Vec<T>`, a collection backed by a vector
+pub const INTRINSIC_TYPE_VECTOR: &str = "vector";
+
+/// Create a new vector with an empty content
+/// `[move] fun vector_new<T>(): Vec<T>`
+pub const INTRINSIC_FUN_VECTOR_NEW: &str = "vector_new";
+
+/// Create a new vector with an empty content (the spec version)
+/// `[spec] fun vector_new<T>(): Vec<T>`
+pub const INTRINSIC_FUN_VECTOR_SPEC_NEW: &str = "vector_spec_new";
+
+/// Get the element at index `i`.
+/// The behavior is undefined if `i` is out of bounds
+/// `[spec] fun vector_get<T>(v: Vec<T>, i: num): T`
+pub const INTRINSIC_FUN_VECTOR_SPEC_GET: &str = "vector_spec_get";
+
+/// Get the number of elements in the vector (the spec version)
+/// `[spec] fun vector_len<T>(v: Vec<T>): num`
+pub const INTRINSIC_FUN_VECTOR_SPEC_LEN: &str = "vector_spec_len";
+
+/// Get the number of elements in the vector
+/// `[move] fun vector_len<T>(v: &Vec<T>): u64`
+pub const INTRINSIC_FUN_VECTOR_LEN: &str = "vector_len";
+
+/// Check whether the vector is empty
+/// `[move] fun vector_is_empty<T>(v: &Vec<T>): bool`
+pub const INTRINSIC_FUN_VECTOR_IS_EMPTY: &str = "vector_is_empty";
+
+/// Add an element to the end of the vector
+/// `[move] fun vector_push_back<T>(v: &mut Vec<T>, e: T)`
+pub const INTRINSIC_FUN_VECTOR_PUSH_BACK: &str = "vector_push_back";
+
+/// Remove the last element of the vector, aborts if the vector is empty
+/// `[move] fun vector_pop_back<T>(v: &mut Vec<T>): T`
+pub const INTRINSIC_FUN_VECTOR_POP_BACK: &str = "vector_pop_back";
+
+/// Immutable borrow of the element at index `i`, aborts if `i` is out of bounds
+/// `[move] fun vector_borrow<T>(v: &Vec<T>, i: u64): &T`
+pub const INTRINSIC_FUN_VECTOR_BORROW: &str = "vector_borrow";
+
+/// Mutable borrow of the element at index `i`, aborts if `i` is out of bounds
+/// `[move] fun vector_borrow_mut<T>(v: &mut Vec<T>, i: u64): &mut T`
+pub const INTRINSIC_FUN_VECTOR_BORROW_MUT: &str = "vector_borrow_mut";
+
+/// Destroys the vector, aborts if the length is not zero.
+/// `[move] fun vector_destroy_empty<T>(v: Vec<T>)`
+pub const INTRINSIC_FUN_VECTOR_DESTROY_EMPTY: &str = "vector_destroy_empty";
+
+/// All intrinsic functions associated with the vector type
+pub static INTRINSIC_TYPE_VECTOR_ASSOC_FUNCTIONS: Lazy<BTreeMap<&'static str, bool>> =
+    Lazy::new(|| {
+        BTreeMap::from([
+            (INTRINSIC_FUN_VECTOR_NEW, true),
+            (INTRINSIC_FUN_VECTOR_SPEC_NEW, false),
+            (INTRINSIC_FUN_VECTOR_SPEC_GET, false),
+            (INTRINSIC_FUN_VECTOR_SPEC_LEN, false),
+            (INTRINSIC_FUN_VECTOR_LEN, true),
+            (INTRINSIC_FUN_VECTOR_IS_EMPTY, true),
+            (INTRINSIC_FUN_VECTOR_PUSH_BACK, true),
+            (INTRINSIC_FUN_VECTOR_POP_BACK, true),
+            (INTRINSIC_FUN_VECTOR_BORROW, true),
+            (INTRINSIC_FUN_VECTOR_BORROW_MUT, true),
+            (INTRINSIC_FUN_VECTOR_DESTROY_EMPTY, true),
+        ])
+    });
+
 /// Checks whether a pragma is valid in a specific spec block.
 pub fn is_pragma_valid_for_block(
     symbols: &SymbolPool,
@@ -223,18 +294,30 @@ pub fn is_pragma_valid_for_block(
                 | BV_PARAM_PROP
                 | BV_RET_PROP
         ),
-        Struct(..) => match pragma {
-            INTRINSIC_PRAGMA | BV_PARAM_PROP => true,
-            _ if INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS.contains_key(pragma) => bag
-                .get(&symbols.make(INTRINSIC_PRAGMA))
-                .map(|v| match v {
-                    PropertyValue::Symbol(s) => symbols.string(*s).as_str() == INTRINSIC_TYPE_MAP,
-                    _ => false,
-                })
-                .unwrap_or(false),
-            // all other cases
-            _ => false,
-        },
+        Struct(..) => {
+            let is_intrinsic_of = |intrinsic_type: &str| {
+                bag.get(&symbols.make(INTRINSIC_PRAGMA))
+                    .map(|v| match v {
+                        PropertyValue::Symbol(s) => symbols.string(*s).as_str() == intrinsic_type,
+                        _ => false,
+                    })
+                    .unwrap_or(false)
+            };
+            match pragma {
+                INTRINSIC_PRAGMA | BV_PARAM_PROP => true,
+                INTRINSIC_FUN_MODULE_PROP => {
+                    is_intrinsic_of(INTRINSIC_TYPE_MAP) || is_intrinsic_of(INTRINSIC_TYPE_VECTOR)
+                }
+                _ if INTRINSIC_TYPE_MAP_ASSOC_FUNCTIONS.contains_key(pragma) => {
+                    is_intrinsic_of(INTRINSIC_TYPE_MAP)
+                }
+                _ if INTRINSIC_TYPE_VECTOR_ASSOC_FUNCTIONS.contains_key(pragma) => {
+                    is_intrinsic_of(INTRINSIC_TYPE_VECTOR)
+                }
+                // all other cases
+                _ => false,
+            }
+        }
         _ => false,
     }
 }
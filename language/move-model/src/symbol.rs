@@ -13,8 +13,10 @@ use std::{
     rc::Rc,
 };
 
+use serde::{Deserialize, Serialize};
+
 /// Representation of a symbol.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Symbol(usize);
 
 impl Symbol {
@@ -0,0 +1,35 @@
+module 0x42::M {
+    struct MyTable<phantom K, phantom V> {}
+
+    spec MyTable {
+        // expect failure
+        pragma intrinsic = map,
+            map_len = 0x42::Ops::length;
+
+        // expect failure
+        pragma intrinsic = map,
+            intrinsic_fun_module = Other,
+            map_len = 0x42::Ops::length;
+    }
+
+    struct MyVec<phantom T> {}
+
+    spec MyVec {
+        // expect failure: the module must be at the same address
+        pragma intrinsic = vector,
+            intrinsic_fun_module = VecOps,
+            vector_len = 0x43::VecOps::length;
+    }
+}
+
+module 0x42::Ops {
+    use 0x42::M::MyTable;
+
+    native fun length<K, V>(t: &MyTable<K, V>): u64;
+}
+
+module 0x43::VecOps {
+    use 0x42::M::MyVec;
+
+    native fun length<T>(v: &MyVec<T>): u64;
+}
@@ -0,0 +1,36 @@
+module 0x42::M {
+    friend 0x42::Ops;
+
+    struct MyTable<phantom K, phantom V> {}
+    struct MyVec<phantom T> {}
+
+    spec MyTable {
+        pragma intrinsic = map,
+            map_new = 0x42::Ops::new,
+            map_len = 0x42::Ops::length,
+            map_spec_len = 0x42::Ops::spec_len;
+    }
+
+    spec MyVec {
+        pragma intrinsic = vector,
+            intrinsic_fun_module = VecOps,
+            vector_new = 0x42::VecOps::empty,
+            vector_len = 0x42::VecOps::length;
+    }
+}
+
+module 0x42::Ops {
+    use 0x42::M::MyTable;
+
+    native fun new<K, V>(): MyTable<K, V>;
+    native fun length<K, V>(t: &MyTable<K, V>): u64;
+
+    spec native fun spec_len<K, V>(t: MyTable<K, V>): num;
+}
+
+module 0x42::VecOps {
+    use 0x42::M::MyVec;
+
+    native fun empty<T>(): MyVec<T>;
+    native fun length<T>(v: &MyVec<T>): u64;
+}
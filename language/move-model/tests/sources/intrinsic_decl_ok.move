@@ -29,7 +29,7 @@ module 0x42::M {
     native fun remove<K, V>(t: &mut MyTable2<K, V>, k: K): V;
 
     spec native fun spec_len2<K, V>(t: MyTable2<K, V>): num;
-    spec native fun spec_del<K, V>(t: MyTable2<K, V>): num;
+    spec native fun spec_del<K, V>(t: MyTable2<K, V>, k: K): MyTable2<K, V>;
     spec native fun spec_has_key<K, V>(t: MyTable2<K, V>, k: K): bool;
 
     spec MyTable2 {
@@ -0,0 +1,43 @@
+module 0x42::M {
+    struct MyTable<phantom K, phantom V> {}
+    struct OtherTable<phantom K, phantom V> {}
+
+    native fun new<K, V>(k: K): MyTable<K, V>;
+    native fun length<K, V>(t: &mut MyTable<K, V>): u64;
+    native fun borrow<K, V>(t: &MyTable<K, V>, k: K): V;
+    native fun contains<K, V>(t: &OtherTable<K, V>, k: K): bool;
+    native fun remove<K, V>(t: &mut MyTable<K, V>, k: K): (K, V);
+
+    spec native fun spec_get<K, V>(t: MyTable<K, V>): V;
+    spec native fun spec_len<K, V>(t: MyTable<K, V>): bool;
+
+    spec MyTable {
+        // expect failure
+        pragma intrinsic = map,
+            map_new = new;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_len = length;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_borrow = borrow;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_has_key = contains;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_del_must_exist = remove;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_spec_get = spec_get;
+
+        // expect failure
+        pragma intrinsic = map,
+            map_spec_len = spec_len;
+    }
+}
@@ -0,0 +1,21 @@
+module 0x42::M {
+    struct MyTable<phantom K, phantom V> {}
+
+    native fun new<K, V>(): MyTable<K, V>;
+    native fun add<K, V>(t: &mut MyTable<K, V>, k: K, v: V);
+    native fun borrow<K, V>(t: &MyTable<K, V>, k: K): &V;
+    native fun remove_return_key<K, V>(t: &mut MyTable<K, V>, k: K): (K, V);
+
+    spec native fun spec_len<K, V>(t: MyTable<K, V>): num;
+    spec native fun spec_get<K, V>(t: MyTable<K, V>, k: K): V;
+
+    spec MyTable {
+        pragma intrinsic = map,
+            map_new = new,
+            map_add_no_override = add,
+            map_borrow = borrow,
+            map_del_return_key = remove_return_key,
+            map_spec_len = spec_len,
+            map_spec_get = spec_get;
+    }
+}
@@ -0,0 +1,21 @@
+module 0x42::V {
+    struct MyVec<phantom T> {}
+
+    native fun empty<T>(): MyVec<T>;
+    native fun length<T>(v: &MyVec<T>): u64;
+    native fun push_back<T>(v: &mut MyVec<T>, e: T);
+    native fun borrow<T>(v: &MyVec<T>, i: u64): &T;
+
+    spec native fun spec_len<T>(v: MyVec<T>): num;
+    spec native fun spec_get<T>(v: MyVec<T>, i: num): T;
+
+    spec MyVec {
+        pragma intrinsic = vector,
+            vector_new = empty,
+            vector_len = length,
+            vector_push_back = push_back,
+            vector_borrow = borrow,
+            vector_spec_len = spec_len,
+            vector_spec_get = spec_get;
+    }
+}
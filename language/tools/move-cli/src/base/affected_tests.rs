@@ -0,0 +1,158 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::{bail, Context};
+use clap::*;
+use move_binary_format::access::ModuleAccess;
+use move_compiler::{
+    compiled_unit::CompiledUnitEnum, diagnostics, unit_test::plan_builder::construct_test_plan,
+    PASS_CFGIR,
+};
+use move_core_types::language_storage::ModuleId;
+use move_package::{
+    compilation::build_plan::BuildPlan, resolution::lock_file::LOCK_FILE_NAME,
+    source_package::layout::SourcePackageLayout, BuildConfig,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// List the unit tests of the package at `path` that may be affected by a change, i.e. the tests
+/// in modules that depend, directly or transitively, on a changed module.  A change to a manifest
+/// or lock file affects every test.
+#[derive(Parser)]
+#[clap(name = "affected-tests")]
+pub struct AffectedTests {
+    /// The changed source files, or git revisions to compare the working tree against (in which
+    /// case every file changed since that revision is considered changed)
+    #[clap(long = "since", required = true, multiple_values = true)]
+    pub since: Vec<String>,
+}
+
+impl AffectedTests {
+    pub fn execute(self, path: Option<PathBuf>, mut config: BuildConfig) -> anyhow::Result<()> {
+        // Find the changed files before rerooting, which changes the working directory.
+        let changed = changed_files(&self.since)?;
+
+        let rerooted_path = reroot_path(path)?;
+        config.test_mode = true;
+        config.dev_mode = true;
+
+        // Build the package, intercepting the compilation to construct its test plan (resolution
+        // and build output goes to stderr to keep stdout to the list of tests).
+        let resolution_graph =
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stderr())?;
+        let root_package = resolution_graph.root_package.package.name;
+        let build_plan = BuildPlan::create(resolution_graph)?;
+        let mut test_plan = None;
+        let compiled =
+            build_plan.compile_with_driver(&mut std::io::stderr(), None, |compiler| {
+                let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
+                let (_, compiler) =
+                    diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
+                let (mut compiler, cfgir) = compiler.into_ast();
+                let compilation_env = compiler.compilation_env();
+                test_plan = construct_test_plan(compilation_env, Some(root_package), &cfgir);
+                let compilation_result = compiler.at_cfgir(cfgir).build();
+                let (units, _) =
+                    diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
+                Ok((files, units))
+            })?;
+
+        let all_affected = changed.iter().any(|file| {
+            file.file_name().map_or(false, |name| {
+                name == SourcePackageLayout::Manifest.path().as_os_str() || name == LOCK_FILE_NAME
+            })
+        });
+
+        // Modules that depend on each module, and the modules defined in changed files.
+        let mut dependents: BTreeMap<ModuleId, Vec<ModuleId>> = BTreeMap::new();
+        let mut frontier = vec![];
+        let units = compiled
+            .root_compiled_units
+            .iter()
+            .chain(compiled.deps_compiled_units.iter().map(|(_, unit)| unit));
+        for unit in units {
+            let CompiledUnitEnum::Module(module) = &unit.unit else {
+                continue;
+            };
+
+            let module = &module.module;
+            for dep in module.immediate_dependencies() {
+                dependents.entry(dep).or_default().push(module.self_id());
+            }
+
+            if let Ok(source_path) = unit.source_path.canonicalize() {
+                if changed.contains(&source_path) {
+                    frontier.push(module.self_id());
+                }
+            }
+        }
+
+        let mut affected = BTreeSet::new();
+        while let Some(module) = frontier.pop() {
+            if affected.insert(module.clone()) {
+                frontier.extend(dependents.remove(&module).into_iter().flatten());
+            }
+        }
+
+        for module_plan in test_plan.into_iter().flatten() {
+            if !all_affected && !affected.contains(&module_plan.module_id) {
+                continue;
+            }
+
+            for test_name in module_plan.tests.keys() {
+                println!(
+                    "0x{}::{}::{}",
+                    module_plan.module_id.address().short_str_lossless(),
+                    module_plan.module_id.name(),
+                    test_name,
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The canonical paths of the files named by `since`, either directly or as the files changed in
+/// the working tree since a git revision.  Changed files that no longer exist are ignored.
+fn changed_files(since: &[String]) -> anyhow::Result<BTreeSet<PathBuf>> {
+    let mut changed = BTreeSet::new();
+    for file_or_rev in since {
+        if Path::new(file_or_rev).exists() {
+            changed.insert(Path::new(file_or_rev).canonicalize()?);
+            continue;
+        }
+
+        let diff = git(&["diff", "--name-only", file_or_rev])
+            .with_context(|| format!("'{}' is neither a file nor a git revision", file_or_rev))?;
+        let toplevel = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+        changed.extend(
+            diff.lines()
+                .filter_map(|file| toplevel.join(file).canonicalize().ok()),
+        );
+    }
+
+    Ok(changed)
+}
+
+fn git(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Running git")?;
+
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
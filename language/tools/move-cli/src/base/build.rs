@@ -3,16 +3,63 @@
 
 use super::reroot_path;
 use clap::*;
-use move_package::{Architecture, BuildConfig};
-use std::path::PathBuf;
+use codespan_reporting::{
+    diagnostic::Severity,
+    files::{Files, SimpleFiles},
+};
+use move_compiler::{
+    compiled_unit::AnnotatedCompiledUnit,
+    diagnostics::{Diagnostics, FilesSourceText},
+    Compiler,
+};
+use move_package::{timings, Architecture, BuildConfig};
+use serde_json::json;
+use std::{collections::HashMap, path::PathBuf};
 
 /// Build the package at `path`. If no path is provided defaults to current directory.
 #[derive(Parser)]
 #[clap(name = "build")]
-pub struct Build;
+pub struct Build {
+    /// Report the time spent in each phase of the build (parsing manifests, building the
+    /// dependency graph, fetching each dependency, writing the lock file, resolution, compilation).
+    #[clap(long = "timings")]
+    pub timings: bool,
+    /// Also write the timings reported by `--timings` to this file, as JSON.
+    #[clap(long = "timings-json", parse(from_os_str), requires = "timings")]
+    pub timings_json: Option<PathBuf>,
+    /// Report compiler diagnostics to stderr as JSON objects, one per line, with the file, span,
+    /// severity and message of each diagnostic, instead of as human-readable text.
+    #[clap(long = "json-errors")]
+    pub json_errors: bool,
+}
 
 impl Build {
     pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        if !self.timings {
+            return Self::build(path, config, self.json_errors);
+        }
+
+        // Building changes the working directory to the package root, so resolve the output path
+        // against the original one first.
+        let timings_json = match self.timings_json {
+            Some(timings_json) => Some(std::env::current_dir()?.join(timings_json)),
+            None => None,
+        };
+
+        timings::start_recording();
+        let result = Self::build(path, config, self.json_errors);
+        let timings = timings::finish_recording().unwrap();
+
+        println!("TIMINGS");
+        print!("{}", timings);
+        if let Some(timings_json) = timings_json {
+            std::fs::write(timings_json, serde_json::to_string_pretty(&timings)?)?;
+        }
+
+        result
+    }
+
+    fn build(path: Option<PathBuf>, config: BuildConfig, json_errors: bool) -> anyhow::Result<()> {
         let rerooted_path = reroot_path(path)?;
         if config.fetch_deps_only {
             let mut config = config;
@@ -25,6 +72,14 @@ impl Build {
         let architecture = config.architecture.unwrap_or(Architecture::Move);
 
         match architecture {
+            Architecture::Move | Architecture::AsyncMove if json_errors => {
+                config.compile_package_with_driver(
+                    &rerooted_path,
+                    &mut std::io::stdout(),
+                    build_and_report_json,
+                )?;
+            }
+
             Architecture::Move | Architecture::AsyncMove => {
                 config.compile_package(&rerooted_path, &mut std::io::stdout())?;
             }
@@ -40,3 +95,62 @@ impl Build {
         Ok(())
     }
 }
+
+/// Compiler driver that reports diagnostics as JSON lines, failing (rather than exiting the
+/// process) if there are any errors.
+fn build_and_report_json(
+    compiler: Compiler,
+) -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)> {
+    let (files, units_res) = compiler.build()?;
+    match units_res {
+        Ok((units, warning_diags)) => {
+            report_json(&files, warning_diags);
+            Ok((files, units))
+        }
+        Err(error_diags) => {
+            report_json(&files, error_diags);
+            anyhow::bail!("Compilation error");
+        }
+    }
+}
+
+/// Print `diags` to stderr, one JSON object per line, ordered by location.  Spans are reported as
+/// 1-based lines and columns, like the human-readable diagnostics.
+fn report_json(sources: &FilesSourceText, diags: Diagnostics) {
+    let mut files = SimpleFiles::new();
+    let mut file_mapping = HashMap::new();
+    for (fhash, (fname, source)) in sources {
+        let id = files.add(fname.as_str(), source.as_str());
+        file_mapping.insert(*fhash, id);
+    }
+
+    let mut diags = diags.into_codespan_format();
+    diags.sort_by_key(|(_, _, (loc, _), _, _)| *loc);
+    diags.dedup();
+
+    for (severity, message, (loc, label), _, notes) in diags {
+        let id = file_mapping[&loc.file_hash()];
+        let start = files.location(id, loc.start() as usize).unwrap();
+        let end = files.location(id, loc.end() as usize).unwrap();
+        let severity = match severity {
+            Severity::Bug => "bug",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        };
+
+        let diag = json!({
+            "file": files.name(id).unwrap(),
+            "span": {
+                "start": { "line": start.line_number, "column": start.column_number },
+                "end": { "line": end.line_number, "column": end.column_number },
+            },
+            "severity": severity,
+            "message": message,
+            "label": label,
+            "notes": notes,
+        });
+        eprintln!("{}", diag);
+    }
+}
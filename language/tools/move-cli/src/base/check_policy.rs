@@ -0,0 +1,45 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::{bail, Context};
+use clap::*;
+use move_package::{resolution::policy::DependencyPolicy, BuildConfig};
+use std::path::PathBuf;
+
+/// Check the transitive dependencies of the package at `path` against a dependency policy, failing
+/// if any of them is denied by it.
+#[derive(Parser)]
+#[clap(name = "check-policy")]
+pub struct CheckPolicy {
+    /// The TOML file listing the rules that dependencies must not match
+    #[clap(parse(from_os_str))]
+    pub policy: PathBuf,
+}
+
+impl CheckPolicy {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        // Read the policy before rerooting, which changes the working directory.
+        let policy = DependencyPolicy::read(&self.policy)?;
+
+        let rerooted_path = reroot_path(path)?;
+        let graph = config.dependency_graph_for_package(&rerooted_path, &mut std::io::stdout())?;
+        let violations = policy
+            .check(&graph, &rerooted_path)
+            .context("Checking dependency policy")?;
+
+        for violation in &violations {
+            println!("{}", violation);
+        }
+
+        if !violations.is_empty() {
+            bail!(
+                "{} dependencies violate the policy in {}",
+                violations.len(),
+                self.policy.display(),
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context;
+use clap::*;
+use move_package::resolution::lock_file::diff::LockDiff;
+use std::{fs::File, path::PathBuf};
+
+/// Report the dependencies added, removed, repinned, or moved to a different source between two
+/// lock files.
+#[derive(Parser)]
+#[clap(name = "diff-deps")]
+pub struct DiffDeps {
+    /// The lock file to compare against
+    #[clap(parse(from_os_str))]
+    pub old_lock: PathBuf,
+    /// The lock file to compare
+    #[clap(parse(from_os_str))]
+    pub new_lock: PathBuf,
+    /// Print the differences as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+impl DiffDeps {
+    pub fn execute(self) -> anyhow::Result<()> {
+        let open = |path: &PathBuf| {
+            File::open(path).with_context(|| format!("Opening lock file {}", path.display()))
+        };
+        let diff = LockDiff::new(&mut open(&self.old_lock)?, &mut open(&self.new_lock)?)?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            print!("{}", diff);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::Context;
+use clap::*;
+use move_package::resolution::lock_file::{graph::LockGraph, LOCK_FILE_NAME};
+use std::{fs::File, path::PathBuf};
+
+/// Print the dependency graph recorded in the lock file of the package at `path`.
+#[derive(Parser)]
+#[clap(name = "graph")]
+pub struct Graph {
+    /// The format to print the graph in: indented trees of dependencies, or GraphViz dot
+    #[clap(long = "format", arg_enum, default_value = "text")]
+    pub format: GraphFormat,
+}
+
+#[derive(Clone, Copy, Debug, ArgEnum)]
+pub enum GraphFormat {
+    Text,
+    Dot,
+}
+
+impl Graph {
+    pub fn execute(self, path: Option<PathBuf>) -> anyhow::Result<()> {
+        let lock_path = reroot_path(path)?.join(LOCK_FILE_NAME);
+        let mut lock = File::open(&lock_path)
+            .with_context(|| format!("Opening lock file {}", lock_path.display()))?;
+        let graph = LockGraph::new(&mut lock)?;
+
+        match self.format {
+            GraphFormat::Text => print!("{}", graph),
+            GraphFormat::Dot => print!("{}", graph.to_dot()),
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,22 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod affected_tests;
 pub mod build;
+pub mod check_policy;
 pub mod coverage;
+pub mod diff_deps;
 pub mod disassemble;
 pub mod docgen;
 pub mod errmap;
+pub mod graph;
 pub mod info;
 pub mod new;
 pub mod prove;
+pub mod resolve;
 pub mod test;
+pub mod verify_reproducible;
+pub mod why;
 
 use move_package::source_package::layout::SourcePackageLayout;
 use std::path::PathBuf;
@@ -0,0 +1,33 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use clap::*;
+use move_package::{resolution::lock_file::LOCK_FILE_NAME, BuildConfig};
+use std::path::PathBuf;
+
+/// Resolve the dependencies of the package at `path` and save the resulting lock file in the
+/// package root.
+#[derive(Parser)]
+#[clap(name = "resolve")]
+pub struct Resolve {
+    /// Print the lock file that resolution produces instead of saving it (any existing lock file
+    /// is left unchanged).
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+impl Resolve {
+    pub fn execute(self, path: Option<PathBuf>, mut config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        if self.dry_run {
+            // Keep progress messages out of the way of the printed lock file
+            let lock = config.lock_for_package(&rerooted_path, &mut std::io::stderr())?;
+            print!("{}", lock);
+        } else {
+            config.lock_file = Some(rerooted_path.join(LOCK_FILE_NAME));
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stdout())?;
+        }
+        Ok(())
+    }
+}
@@ -44,6 +44,9 @@ pub struct Test {
     /// contains this string in its fully qualified (<addr>::<module_name>::<fn_name>) name.
     #[clap(name = "filter")]
     pub filter: Option<String>,
+    /// The same filter string, given as an option instead
+    #[clap(long = "filter", value_name = "FILTER", conflicts_with = "filter")]
+    pub filter_option: Option<String>,
     /// List all tests
     #[clap(name = "list", short = 'l', long = "list")]
     pub list: bool,
@@ -96,6 +99,7 @@ impl Test {
         let Self {
             gas_limit,
             filter,
+            filter_option,
             list,
             num_threads,
             report_statistics,
@@ -109,7 +113,7 @@ impl Test {
         } = self;
         let unit_test_config = UnitTestingConfig {
             gas_limit,
-            filter,
+            filter: filter.or(filter_option),
             list,
             num_threads,
             report_statistics,
@@ -0,0 +1,86 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::bail;
+use clap::*;
+use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+use tempfile::tempdir;
+
+/// Check that building the package at `path` is reproducible, by building it (and its
+/// dependencies) from scratch twice and comparing the resulting bytecode.
+#[derive(Parser)]
+#[clap(name = "verify-reproducible")]
+pub struct VerifyReproducible;
+
+impl VerifyReproducible {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        let bytecode_version = config.bytecode_version;
+
+        let mut builds = vec![];
+        for _ in 0..2 {
+            let install_dir = tempdir()?;
+            let config = BuildConfig {
+                install_dir: Some(install_dir.path().to_path_buf()),
+                force_recompilation: true,
+                ..config.clone()
+            };
+            let package = config.compile_package(&rerooted_path, &mut std::io::sink())?;
+            builds.push(serialized_units(&package, bytecode_version));
+        }
+
+        let second = builds.pop().unwrap();
+        let first = builds.pop().unwrap();
+        let mismatches: BTreeSet<_> = first
+            .keys()
+            .chain(second.keys())
+            .filter(|unit| first.get(*unit) != second.get(*unit))
+            .map(|(package, unit)| format!("{}::{}", package, unit))
+            .collect();
+
+        if !mismatches.is_empty() {
+            let mismatches: Vec<_> = mismatches.into_iter().collect();
+            bail!(
+                "Building the package twice produced different bytecode for: {}",
+                mismatches.join(", ")
+            );
+        }
+
+        println!(
+            "Bytecode for {} compiled unit(s) is reproducible",
+            first.len()
+        );
+        Ok(())
+    }
+}
+
+/// Serialized bytecode of all the units in `package` (including dependencies), keyed by the name
+/// of the package they belong to and their own name.
+fn serialized_units(
+    package: &CompiledPackage,
+    bytecode_version: Option<u32>,
+) -> BTreeMap<(String, String), Vec<u8>> {
+    let root_name = package.compiled_package_info.package_name;
+    package
+        .root_compiled_units
+        .iter()
+        .map(|unit| (root_name, unit))
+        .chain(
+            package
+                .deps_compiled_units
+                .iter()
+                .map(|(package_name, unit)| (*package_name, unit)),
+        )
+        .map(|(package_name, unit)| {
+            (
+                (package_name.to_string(), unit.unit.name().to_string()),
+                unit.unit.serialize(bytecode_version),
+            )
+        })
+        .collect()
+}
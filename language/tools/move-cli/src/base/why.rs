@@ -0,0 +1,57 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::reroot_path;
+use anyhow::bail;
+use clap::*;
+use move_package::{source_package::parsed_manifest::PackageName, BuildConfig};
+use std::path::PathBuf;
+
+/// Explain why a package is included in the dependency graph, by printing every path from the
+/// package at `path` leading to it.
+#[derive(Parser)]
+#[clap(name = "why")]
+pub struct Why {
+    /// The name of the dependency to explain.
+    pub dependency: String,
+    /// Also consider paths that go through dev-dependencies.
+    #[clap(long = "dev")]
+    pub dev: bool,
+}
+
+impl Why {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        let graph =
+            config.locked_dependency_graph_for_package(&rerooted_path, &mut std::io::stdout())?;
+
+        let dependency = PackageName::from(self.dependency.as_str());
+        let Some(paths) = graph.paths_to(dependency, self.dev) else {
+            bail!("Package '{}' is not in the dependency graph", dependency);
+        };
+
+        if paths.is_empty() {
+            let dev_paths = graph.paths_to(dependency, true).unwrap_or_default();
+            if !self.dev && !dev_paths.is_empty() {
+                println!(
+                    "Package '{}' is only included as a dev-dependency, use `--dev` to show the \
+                     paths leading to it",
+                    dependency,
+                );
+            } else {
+                println!(
+                    "Package '{}' is only included as a dev-dependency of other dependencies, \
+                     which are not built for this package",
+                    dependency,
+                );
+            }
+        }
+
+        for path in paths {
+            let path: Vec<_> = path.iter().map(PackageName::as_str).collect();
+            println!("{}", path.join(" -> "));
+        }
+
+        Ok(())
+    }
+}
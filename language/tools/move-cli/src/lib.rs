@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use base::{
-    build::Build, coverage::Coverage, disassemble::Disassemble, docgen::Docgen, errmap::Errmap,
-    info::Info, new::New, prove::Prove, test::Test,
+    affected_tests::AffectedTests, build::Build, check_policy::CheckPolicy, coverage::Coverage,
+    diff_deps::DiffDeps, disassemble::Disassemble, docgen::Docgen, errmap::Errmap, graph::Graph,
+    info::Info, new::New, prove::Prove, resolve::Resolve, test::Test,
+    verify_reproducible::VerifyReproducible, why::Why,
 };
 use move_package::BuildConfig;
 
@@ -62,15 +64,22 @@ pub struct MoveCLI {
 
 #[derive(Parser)]
 pub enum Command {
+    AffectedTests(AffectedTests),
     Build(Build),
+    CheckPolicy(CheckPolicy),
     Coverage(Coverage),
+    DiffDeps(DiffDeps),
     Disassemble(Disassemble),
     Docgen(Docgen),
     Errmap(Errmap),
+    Graph(Graph),
     Info(Info),
     New(New),
     Prove(Prove),
+    Resolve(Resolve),
     Test(Test),
+    VerifyReproducible(VerifyReproducible),
+    Why(Why),
     /// Execute a sandbox command.
     #[clap(name = "sandbox")]
     Sandbox {
@@ -104,20 +113,27 @@ pub fn run_cli(
     //         1. It's still using the old CostTable.
     //         2. The CostTable only affects sandbox runs, but not unit tests, which use a unit cost table.
     match cmd {
+        Command::AffectedTests(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Build(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::CheckPolicy(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Coverage(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::DiffDeps(c) => c.execute(),
         Command::Disassemble(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Docgen(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Errmap(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Graph(c) => c.execute(move_args.package_path),
         Command::Info(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::New(c) => c.execute_with_defaults(move_args.package_path),
         Command::Prove(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Resolve(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Test(c) => c.execute(
             move_args.package_path,
             move_args.build_config,
             natives,
             Some(cost_table.clone()),
         ),
+        Command::VerifyReproducible(c) => c.execute(move_args.package_path, move_args.build_config),
+        Command::Why(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Sandbox { storage_dir, cmd } => cmd.handle_command(
             natives,
             cost_table,
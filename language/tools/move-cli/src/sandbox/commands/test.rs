@@ -20,11 +20,11 @@ use move_package::{
 use std::{
     collections::{BTreeMap, HashMap},
     env,
-    fmt::Write as FmtWrite,
+    fmt::{self, Write as FmtWrite},
     fs::{self, File},
     io::{self, BufRead, Write},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, ExitStatus},
 };
 use tempfile::tempdir;
 
@@ -53,6 +53,78 @@ const MOVE_VM_TRACING_ENV_VAR_NAME: &str = "MOVE_VM_TRACE";
 /// be produced.
 const DEFAULT_TRACE_FILE: &str = "trace";
 
+/// Prefix of lines in `args.txt` that set how the next command is expected to terminate, either
+/// `! exit <code>` or (on unix) `! signal <number>`. Commands without such a line can terminate in
+/// any way.
+const EXPECTED_TERMINATION_PREFIX: char = '!';
+
+/// How a command run by a test terminated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Termination {
+    /// Exited normally, with the given exit code
+    Exit(i32),
+    /// Killed by the given signal (unix only)
+    Signal(i32),
+}
+
+impl Termination {
+    fn parse(expectation: &str) -> anyhow::Result<Self> {
+        let words: Vec<_> = expectation.split_whitespace().collect();
+        Ok(match words.as_slice() {
+            ["exit", code] => Termination::Exit(code.parse()?),
+            ["signal", signal] => Termination::Signal(signal.parse()?),
+            _ => anyhow::bail!(
+                "Invalid expected termination `{}`, expected `exit <code>` or `signal <number>`",
+                expectation
+            ),
+        })
+    }
+
+    fn of(status: ExitStatus) -> Option<Self> {
+        if let Some(code) = status.code() {
+            return Some(Termination::Exit(code));
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return Some(Termination::Signal(signal));
+            }
+        }
+
+        None
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Exit(code) => write!(f, "exit code {}", code),
+            Termination::Signal(signal) => write!(f, "signal {}", signal),
+        }
+    }
+}
+
+/// Describes how the command `cmd` terminated if that is not the `expected` termination
+fn unexpected_termination(
+    cmd: &str,
+    expected: Option<Termination>,
+    status: ExitStatus,
+) -> Option<String> {
+    let expected = expected?;
+    let actual = Termination::of(status);
+    if actual == Some(expected) {
+        return None;
+    }
+
+    let actual = actual.map_or_else(|| status.to_string(), |t| t.to_string());
+    Some(format!(
+        "Command `{}` terminated with {} instead of {}",
+        cmd, actual, expected
+    ))
+}
+
 fn collect_coverage(
     trace_file: &Path,
     build_dir: &Path,
@@ -212,6 +284,8 @@ pub fn run_one(
             .output()?;
     }
     let mut output = "".to_string();
+    let mut expected_termination = None;
+    let mut unexpected_terminations = vec![];
 
     // always use the absolute path for the trace file as we may change dirs in the process
     let trace_file = if track_cov {
@@ -239,6 +313,11 @@ pub fn run_one(
                 command.current_dir(exe_dir);
             }
             let cmd_output = command.output()?;
+            unexpected_terminations.extend(unexpected_termination(
+                external_cmd,
+                expected_termination.take(),
+                cmd_output.status,
+            ));
 
             writeln!(&mut output, "External Command `{}`:", external_cmd)?;
             output += std::str::from_utf8(&cmd_output.stdout)?;
@@ -247,6 +326,11 @@ pub fn run_one(
             continue;
         }
 
+        if let Some(expectation) = args_line.strip_prefix(EXPECTED_TERMINATION_PREFIX) {
+            expected_termination = Some(Termination::parse(expectation)?);
+            continue;
+        }
+
         if args_line.starts_with('#') {
             // allow comments in args.txt
             continue;
@@ -271,6 +355,11 @@ pub fn run_one(
         }
 
         let cmd_output = cli_command_template().args(args_iter).output()?;
+        unexpected_terminations.extend(unexpected_termination(
+            &args_line,
+            expected_termination.take(),
+            cmd_output.status,
+        ));
         writeln!(&mut output, "Command `{}`:", args_line)?;
         output += std::str::from_utf8(&cmd_output.stdout)?;
         output += std::str::from_utf8(&cmd_output.stderr)?;
@@ -328,6 +417,10 @@ pub fn run_one(
         t.close()?;
     }
 
+    if !unexpected_terminations.is_empty() {
+        anyhow::bail!(unexpected_terminations.join("\n"))
+    }
+
     // compare output and exp_file
     let update_baseline = read_env_update_baseline();
     let exp_path = args_path.with_extension(EXP_EXT);
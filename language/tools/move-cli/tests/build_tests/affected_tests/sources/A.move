@@ -0,0 +1 @@
+module P::A { public fun a(): u64 { 1 } #[test] fun test_A() { } }
@@ -0,0 +1 @@
+module P::B { use P::A; public fun b(): u64 { A::a() + 1 } #[test] fun test_B() { } }
@@ -0,0 +1 @@
+module P::C { public fun c(): u64 { 3 } #[test] fun test_C() { } }
@@ -0,0 +1,7 @@
+module Dep::Dep {
+    struct S has drop { x: u64 }
+
+    public fun new(x: u64): S {
+        S { x }
+    }
+}
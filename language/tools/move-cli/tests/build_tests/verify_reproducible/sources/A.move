@@ -0,0 +1,10 @@
+module A::A {
+    use Dep::Dep;
+
+    const E: u64 = 1;
+
+    public fun f(x: u64) {
+        assert!(x > 0, E);
+        let _ = Dep::new(x);
+    }
+}
@@ -3,7 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_cli::sandbox::commands::test;
-use std::{env, path::PathBuf, process::Command};
+use move_compiler::command_line::COLOR_MODE_ENV_VAR;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 pub const CLI_METATEST_PATH: [&str; 3] = ["tests", "metatests", "args.txt"];
 
@@ -52,3 +57,399 @@ fn cross_process_locking_git_deps() {
         .expect("Package2 failed");
     handle.join().unwrap();
 }
+
+/// Initialize a git repository at `path` and commit everything in it, returning the commit's hash.
+fn commit_git_repo(path: &Path) -> String {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .current_dir(path)
+            .args(args)
+            .output()
+            .expect("git failed");
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&[
+        "-c",
+        "user.name=Move",
+        "-c",
+        "user.email=move@example.com",
+        "commit",
+        "--quiet",
+        "-m",
+        "Commit",
+    ]);
+    git(&["rev-parse", "HEAD"]).trim().to_string()
+}
+
+#[test]
+fn build_timings_git_dep() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    // A git repository containing the dependency
+    fs::create_dir_all(tmp.path().join("dep/sources")).unwrap();
+    fs::write(
+        tmp.path().join("dep/Move.toml"),
+        "[package]\nname = \"Dep\"\nversion = \"0.0.0\"\n\n[addresses]\nDep = \"0x3\"\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("dep/sources/Dep.move"),
+        "module Dep::Dep { public fun f(): u64 { 42 } }\n",
+    )
+    .unwrap();
+    let rev = commit_git_repo(&tmp.path().join("dep"));
+
+    // The package depending on it
+    let root = tmp.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n\
+             [dependencies]\nDep = {{ git = \"file://{}\", rev = \"{}\" }}\n",
+            tmp.path().join("dep").display(),
+            rev,
+        ),
+    )
+    .unwrap();
+
+    let timings_json = tmp.path().join("timings.json");
+    let output = Command::new(get_cli_binary_path())
+        .current_dir(&root)
+        .env("MOVE_HOME", tmp.path().join("move_home"))
+        .args(["build", "--timings", "--timings-json"])
+        .arg(&timings_json)
+        .output()
+        .expect("build failed");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("TIMINGS"));
+
+    let timings: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(timings_json).unwrap()).unwrap();
+    let entries = timings["entries"].as_array().unwrap();
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry["phase"] == "fetch" && entry["package"] == "Dep"),
+        "{:#}",
+        timings,
+    );
+}
+
+#[test]
+fn resolve_dry_run_leaves_lock_unchanged() {
+    let tmp = tempfile::tempdir().unwrap();
+    for dep in ["A", "B"] {
+        let dep_path = tmp.path().join(dep);
+        fs::create_dir_all(dep_path.join("sources")).unwrap();
+        fs::write(
+            dep_path.join("Move.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.0.0\"\n", dep),
+        )
+        .unwrap();
+    }
+
+    let root = tmp.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    let manifest = |deps: &[&str]| {
+        let deps: Vec<_> = deps
+            .iter()
+            .map(|dep| format!("{} = {{ local = \"../{}\" }}\n", dep, dep))
+            .collect();
+        fs::write(
+            root.join("Move.toml"),
+            format!(
+                "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n[dependencies]\n{}",
+                deps.concat(),
+            ),
+        )
+        .unwrap();
+    };
+    let resolve = |args: &[&str]| {
+        let output = Command::new(get_cli_binary_path())
+            .current_dir(&root)
+            .arg("resolve")
+            .args(args)
+            .output()
+            .expect("resolve failed");
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    manifest(&["A"]);
+    resolve(&[]);
+    let lock = fs::read_to_string(root.join("Move.lock")).unwrap();
+    assert!(lock.contains("name = \"A\""), "{}", lock);
+
+    manifest(&["A", "B"]);
+    let dry_run_lock = resolve(&["--dry-run"]);
+    assert!(dry_run_lock.contains("name = \"A\""), "{}", dry_run_lock);
+    assert!(dry_run_lock.contains("name = \"B\""), "{}", dry_run_lock);
+    assert_eq!(fs::read_to_string(root.join("Move.lock")).unwrap(), lock);
+}
+
+/// Writes a git repository at `repo` holding two packages, `A` and `B`, under `pkgs/`, and a
+/// package at `root` that depends on `B` from that repository with the given `subdir`.
+fn multi_package_git_repo(repo: &Path, root: &Path, subdir: &str) {
+    for (name, addr) in [("A", "0x3"), ("B", "0x4")] {
+        let pkg = repo.join("pkgs").join(name);
+        fs::create_dir_all(pkg.join("sources")).unwrap();
+        fs::write(
+            pkg.join("Move.toml"),
+            format!(
+                "[package]\nname = \"{0}\"\nversion = \"0.0.0\"\n\n[addresses]\n{0} = \"{1}\"\n",
+                name, addr,
+            ),
+        )
+        .unwrap();
+        fs::write(
+            pkg.join("sources").join(format!("{}.move", name)),
+            format!("module {0}::{0} {{ public fun f(): u64 {{ 42 }} }}\n", name),
+        )
+        .unwrap();
+    }
+    let rev = commit_git_repo(repo);
+
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n\
+             [dependencies]\nB = {{ git = \"file://{}\", rev = \"{}\", subdir = \"{}\" }}\n",
+            repo.display(),
+            rev,
+            subdir,
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn build_git_dep_in_subdir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("root");
+    multi_package_git_repo(&tmp.path().join("repo"), &root, "pkgs/B");
+
+    let output = Command::new(get_cli_binary_path())
+        .current_dir(&root)
+        .env("MOVE_HOME", tmp.path().join("move_home"))
+        .arg("build")
+        .output()
+        .expect("build failed");
+    assert!(output.status.success(), "{:?}", output);
+
+    let lock = fs::read_to_string(root.join("Move.lock")).unwrap();
+    assert!(lock.contains("subdir = \"pkgs/B\""), "{}", lock);
+}
+
+#[test]
+fn build_git_dep_in_subdir_without_manifest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("root");
+    multi_package_git_repo(&tmp.path().join("repo"), &root, "pkgs/C");
+
+    let output = Command::new(get_cli_binary_path())
+        .current_dir(&root)
+        .env("MOVE_HOME", tmp.path().join("move_home"))
+        .arg("build")
+        .output()
+        .expect("build failed");
+    assert!(!output.status.success(), "{:?}", output);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("does not contain a package manifest for 'B'"),
+        "{}",
+        stderr,
+    );
+}
+
+#[test]
+fn build_json_errors() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pkg = tmp.path();
+    fs::create_dir_all(pkg.join("sources")).unwrap();
+    fs::write(
+        pkg.join("Move.toml"),
+        "[package]\nname = \"Errors\"\nversion = \"0.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        pkg.join("sources/M.move"),
+        "module 0x2::M {\n    public fun f(): u64 { true }\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_cli_binary_path())
+        .current_dir(pkg)
+        .args(["build", "--json-errors"])
+        .output()
+        .expect("build failed");
+    assert!(!output.status.success(), "{:?}", output);
+
+    // Diagnostics are interleaved with other output on stderr
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let diags: Vec<serde_json::Value> = stderr
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    assert_eq!(diags.len(), 1, "{}", stderr);
+
+    let diag = &diags[0];
+    assert!(
+        diag["file"].as_str().unwrap().ends_with("M.move"),
+        "{}",
+        diag
+    );
+    assert_eq!(diag["severity"], "error", "{}", diag);
+    assert_eq!(diag["label"], "Invalid return expression", "{}", diag);
+    assert_eq!(diag["span"]["start"]["line"], 2, "{}", diag);
+    assert_eq!(diag["span"]["start"]["column"], 27, "{}", diag);
+    assert_eq!(diag["span"]["end"]["line"], 2, "{}", diag);
+    assert_eq!(diag["span"]["end"]["column"], 31, "{}", diag);
+}
+
+#[test]
+fn check_policy_denies_git_url_prefix() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    // Two git repositories, each containing one dependency
+    let mut deps = String::new();
+    for (name, addr) in [("Trusted", "0x3"), ("Untrusted", "0x4")] {
+        let repo = tmp.path().join(name.to_lowercase());
+        fs::create_dir_all(repo.join("sources")).unwrap();
+        fs::write(
+            repo.join("Move.toml"),
+            format!(
+                "[package]\nname = \"{0}\"\nversion = \"0.0.0\"\n\n[addresses]\n{0} = \"{1}\"\n",
+                name, addr,
+            ),
+        )
+        .unwrap();
+        let rev = commit_git_repo(&repo);
+        deps.push_str(&format!(
+            "{} = {{ git = \"file://{}\", rev = \"{}\" }}\n",
+            name,
+            repo.display(),
+            rev,
+        ));
+    }
+
+    let root = tmp.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        format!(
+            "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n[dependencies]\n{}",
+            deps,
+        ),
+    )
+    .unwrap();
+
+    let check_policy = |prefix: &str| {
+        let policy = tmp.path().join("policy.toml");
+        fs::write(&policy, format!("[[deny]]\ngit = \"{}\"\n", prefix)).unwrap();
+        Command::new(get_cli_binary_path())
+            .current_dir(&root)
+            .env("MOVE_HOME", tmp.path().join("move_home"))
+            .arg("check-policy")
+            .arg(&policy)
+            .output()
+            .expect("check-policy failed")
+    };
+
+    let denied = check_policy(&format!(
+        "file://{}",
+        tmp.path().join("untrusted").display()
+    ));
+    assert!(!denied.status.success(), "{:?}", denied);
+    let stdout = String::from_utf8(denied.stdout).unwrap();
+    assert!(
+        stdout.contains("Dependency 'Untrusted' is denied by rule"),
+        "{}",
+        stdout,
+    );
+    assert!(!stdout.contains("'Trusted'"), "{}", stdout);
+
+    let allowed = check_policy("https://github.com/untrusted-org/");
+    assert!(allowed.status.success(), "{:?}", allowed);
+}
+
+#[cfg(unix)]
+#[test]
+fn expected_termination_reports_signal() {
+    let tmp = tempfile::tempdir().unwrap();
+    fs::write(tmp.path().join("abort.sh"), "kill -ABRT $$\n").unwrap();
+    let args_path = tmp.path().join("args.txt");
+    fs::write(&args_path, "! exit 1\n> sh abort.sh\n").unwrap();
+
+    let err = test::run_one(&args_path, &get_cli_binary_path(), false, false)
+        .expect_err("termination mismatch not reported");
+    assert_eq!(
+        err.to_string(),
+        "Command `sh abort.sh` terminated with signal 6 instead of exit code 1"
+    );
+}
+
+#[test]
+fn build_reports_module_defined_by_two_dependencies() {
+    let tmp = tempfile::tempdir().unwrap();
+    for dep in ["A", "B"] {
+        let dep_path = tmp.path().join(dep);
+        fs::create_dir_all(dep_path.join("sources")).unwrap();
+        fs::write(
+            dep_path.join("Move.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.0.0\"\n", dep),
+        )
+        .unwrap();
+        fs::write(
+            dep_path.join("sources").join("foo.move"),
+            "module 0x2::foo {}\n",
+        )
+        .unwrap();
+    }
+
+    let root = tmp.path().join("root");
+    fs::create_dir_all(root.join("sources")).unwrap();
+    fs::write(
+        root.join("Move.toml"),
+        "[package]\nname = \"Root\"\nversion = \"0.0.0\"\n\n[dependencies]\n\
+         A = { local = \"../A\" }\nB = { local = \"../B\" }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_cli_binary_path())
+        .current_dir(&root)
+        .env(COLOR_MODE_ENV_VAR, "NONE")
+        .arg("build")
+        .output()
+        .expect("build failed to run");
+    assert!(!output.status.success(), "{:?}", output);
+
+    // Which of the two definitions is reported as the duplicate depends on compilation order
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let (first, second) =
+        if stderr.contains("Duplicate definition for module '0x2::foo' in package 'A'") {
+            ("B", "A")
+        } else {
+            ("A", "B")
+        };
+    for expected in [
+        format!(
+            "Duplicate definition for module '0x2::foo' in package '{}'",
+            second
+        ),
+        format!(
+            "Module previously defined here, with '0x2::foo' in package '{}'",
+            first
+        ),
+    ] {
+        assert!(stderr.contains(&expected), "{}", stderr);
+    }
+}
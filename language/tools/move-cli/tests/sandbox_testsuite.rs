@@ -7,6 +7,11 @@ use move_cli::sandbox::commands::test;
 use std::path::{Path, PathBuf};
 
 fn run_all(args_path: &Path) -> datatest_stable::Result<()> {
+    // tests marked UNIX_ONLY run external commands that are only available on unix
+    if cfg!(not(unix)) && args_path.parent().unwrap().join("UNIX_ONLY").exists() {
+        return Ok(());
+    }
+
     let cli_exe = env!("CARGO_BIN_EXE_move");
     let use_temp_dir = !args_path.parent().unwrap().join("NO_TEMPDIR").exists();
     test::run_one(
@@ -8,17 +8,23 @@ pub mod compilation;
 pub mod package_hooks;
 pub mod resolution;
 pub mod source_package;
+pub mod timings;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use clap::*;
+use move_compiler::{compiled_unit::AnnotatedCompiledUnit, diagnostics::FilesSourceText, Compiler};
 use move_core_types::account_address::AccountAddress;
 use move_model::model::GlobalEnv;
-use resolution::{dependency_graph::DependencyGraph, lock_file::LockFile};
+use resolution::{
+    dependency_graph::DependencyGraph,
+    lock_file::{diff::LockDiff, schema, verify_digests, LockFile, LOCK_FILE_NAME},
+};
 use serde::{Deserialize, Serialize};
 use source_package::layout::SourcePackageLayout;
 use std::{
     collections::BTreeMap,
     fmt,
+    fs::{self, File},
     io::Write,
     path::{Path, PathBuf},
 };
@@ -140,6 +146,23 @@ pub struct BuildConfig {
     #[clap(long = "skip-fetch-latest-git-deps", global = true)]
     pub skip_fetch_latest_git_deps: bool,
 
+    /// Build the dependency graph from the package's lock file (if it has one), instead of
+    /// re-reading the manifests of all its transitive dependencies.  If the lock file does not
+    /// reflect changes to the package's manifest, its dependencies are resolved again, but they
+    /// must not differ from the ones in the lock file
+    #[clap(long = "locked", global = true)]
+    pub locked: bool,
+
+    /// Fail instead of updating the package's lock file if its dependencies are out of date (or
+    /// if there is no lock file), and never write the lock file
+    #[clap(long = "frozen", global = true)]
+    pub frozen: bool,
+
+    /// Source the transitive dependencies of a dependency from the lock file committed in its
+    /// package (if it has one), instead of the manifests of those transitive dependencies
+    #[clap(long = "respect-dep-locks", global = true)]
+    pub respect_dep_locks: bool,
+
     /// Bytecode version to compile move code
     #[clap(long = "bytecode-version", global = true)]
     pub bytecode_version: Option<u32>,
@@ -161,7 +184,31 @@ impl BuildConfig {
         let bytecode_version = self.bytecode_version;
         let resolved_graph = self.resolution_graph_for_package(path, writer)?;
         let mutx = PackageLock::lock();
-        let ret = BuildPlan::create(resolved_graph)?.compile(bytecode_version, writer);
+        let ret = timings::time(timings::COMPILE, None, || {
+            BuildPlan::create(resolved_graph)?.compile(bytecode_version, writer)
+        });
+        mutx.unlock();
+        ret
+    }
+
+    /// Compile the package at `path` or the containing Move package, like `compile_package`, but
+    /// running the compiler through `compiler_driver` (e.g., to report diagnostics differently).
+    pub fn compile_package_with_driver<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+        compiler_driver: impl FnMut(Compiler) -> Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        let bytecode_version = self.bytecode_version;
+        let resolved_graph = self.resolution_graph_for_package(path, writer)?;
+        let mutx = PackageLock::lock();
+        let ret = timings::time(timings::COMPILE, None, || {
+            BuildPlan::create(resolved_graph)?.compile_with_driver(
+                writer,
+                bytecode_version,
+                compiler_driver,
+            )
+        });
         mutx.unlock();
         ret
     }
@@ -225,40 +272,196 @@ impl BuildConfig {
     }
 
     pub fn resolution_graph_for_package<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<ResolvedGraph> {
+        let lock_path = self.lock_file.clone();
+        let frozen = self.frozen;
+        self.resolve_package(path, writer, |lock| match &lock_path {
+            Some(lock_path) if !frozen => lock.commit(lock_path),
+            _ => Ok(()),
+        })
+    }
+
+    /// Resolves the package at `path` like `resolution_graph_for_package`, but returns the contents
+    /// of the resulting lock file instead of saving it.
+    pub fn lock_for_package<W: Write>(self, path: &Path, writer: &mut W) -> Result<String> {
+        let mut contents = String::new();
+        self.resolve_package(path, writer, |lock| {
+            contents = lock.read_contents()?;
+            Ok(())
+        })?;
+        Ok(contents)
+    }
+
+    /// Resolves the package at `path`, passing its lock file to `on_lock` as soon as it has been
+    /// written (before the rest of the package is resolved).
+    fn resolve_package<W: Write>(
         mut self,
         path: &Path,
         writer: &mut W,
+        on_lock: impl FnOnce(LockFile) -> Result<()>,
     ) -> Result<ResolvedGraph> {
         if self.test_mode {
             self.dev_mode = true;
         }
         let path = SourcePackageLayout::try_find_root(path)?;
-        let toml_manifest =
-            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let toml_manifest = timings::time(timings::PARSE_MANIFEST, None, || {
+            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))
+        })?;
         let mutx = PackageLock::lock();
 
         // This should be locked as it inspects the environment for `MOVE_HOME` which could
         // possibly be set by a different process in parallel.
         let mut lock = LockFile::new(&path)?;
+        let manifest = timings::time(timings::PARSE_MANIFEST, None, || {
+            manifest_parser::parse_source_manifest(toml_manifest)
+        })?;
+
+        let lock_path = path.join(LOCK_FILE_NAME);
+        let stale = self.locked
+            && lock_path.is_file()
+            && LockFile::is_stale(&path.join(SourcePackageLayout::Manifest.path()))?;
+
+        let dependency_graph = timings::time(timings::DEPENDENCY_GRAPH, None, || {
+            if stale {
+                eprintln!(
+                    "Warning: lock file {} may not reflect changes to the package's manifest, \
+                     re-resolving its dependencies",
+                    lock_path.display(),
+                );
+            }
+
+            if self.locked && lock_path.is_file() && !stale {
+                let mismatches = verify_digests(&mut File::open(&lock_path)?, &path)
+                    .with_context(|| format!("Verifying lock file {}", lock_path.display()))?;
+                if !mismatches.is_empty() {
+                    bail!(
+                        "Dependencies changed since lock file {} was written:\n{}",
+                        lock_path.display(),
+                        mismatches
+                            .iter()
+                            .map(|m| m.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+
+                let mut lock_file = File::open(&lock_path)?;
+                DependencyGraph::read_from_lock(path.clone(), manifest.clone(), &mut lock_file)
+                    .with_context(|| format!("Reading lock file {}", lock_path.display()))
+            } else {
+                DependencyGraph::new(
+                    &manifest,
+                    path.clone(),
+                    self.skip_fetch_latest_git_deps,
+                    self.respect_dep_locks,
+                    writer,
+                )
+            }
+        })?;
+
+        // Packages pinned by the lock files of dependencies need to be fetched from those pins
+        // during resolution as well.
+        let dependency_pins = if self.respect_dep_locks {
+            dependency_graph.package_sources()
+        } else {
+            BTreeMap::new()
+        };
+
+        timings::time(timings::WRITE_LOCK, None, || {
+            dependency_graph.write_to_lock(&mut lock)?;
+
+            if self.frozen {
+                // The lock file that would be saved, which --frozen prevents changing
+                let lock_path = match &self.lock_file {
+                    Some(lock_file) => lock_file.clone(),
+                    None => lock_path.clone(),
+                };
+                if !lock_path.is_file() {
+                    bail!(
+                        "Lock file {} does not exist, and --frozen prevents creating it",
+                        lock_path.display(),
+                    );
+                }
+
+                let diff = LockDiff::new(
+                    &mut File::open(&lock_path)?,
+                    &mut lock.read_contents()?.as_bytes(),
+                )?;
+                if !diff.is_empty() {
+                    bail!(
+                        "Lock file {} is out of date, and --frozen prevents updating it:\n{}",
+                        lock_path.display(),
+                        diff,
+                    );
+                }
+            }
+
+            if stale {
+                let old = fs::read_to_string(&lock_path)?;
+                let new = lock.read_contents()?;
+                if schema::strip_header(&old)? != schema::strip_header(&new)? {
+                    bail!(
+                        "Resolving dependencies again changed them from the ones in lock file {}, \
+                         which --locked prevents",
+                        lock_path.display(),
+                    );
+                }
+            }
+
+            on_lock(lock)
+        })?;
+
+        let ret = timings::time(timings::RESOLVE, None, || {
+            ResolutionGraph::with_dependency_pins(manifest, path, self, dependency_pins, writer)?
+                .resolve()
+        })?;
+
+        mutx.unlock();
+        Ok(ret)
+    }
+
+    /// Builds the transitive dependency graph of the package at `path`, including its
+    /// dev-dependencies, without resolving it for this build configuration.
+    pub fn dependency_graph_for_package<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<DependencyGraph> {
+        let path = SourcePackageLayout::try_find_root(path)?;
+        let toml_manifest =
+            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let mutx = PackageLock::lock();
         let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
 
         let dependency_graph = DependencyGraph::new(
             &manifest,
-            path.clone(),
+            path,
             self.skip_fetch_latest_git_deps,
+            self.respect_dep_locks,
             writer,
         )?;
 
-        dependency_graph.write_to_lock(&mut lock)?;
-        if let Some(lock_path) = &self.lock_file {
-            lock.commit(lock_path)?;
-        }
+        mutx.unlock();
+        Ok(dependency_graph)
+    }
 
-        let resolution_graph = ResolutionGraph::new(manifest, path, self, writer)?;
-        let ret = resolution_graph.resolve()?;
+    /// Resolves the package at `path` like `lock_for_package`, and reads its transitive dependency
+    /// graph back from the resulting lock file.
+    pub fn locked_dependency_graph_for_package<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<DependencyGraph> {
+        let path = SourcePackageLayout::try_find_root(path)?;
+        let toml_manifest =
+            self.parse_toml_manifest(path.join(SourcePackageLayout::Manifest.path()))?;
+        let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
 
-        mutx.unlock();
-        Ok(ret)
+        let lock = self.lock_for_package(&path, writer)?;
+        DependencyGraph::read_from_lock(path, manifest, &mut lock.as_bytes())
     }
 
     fn parse_toml_manifest(&self, path: PathBuf) -> Result<toml::Value> {
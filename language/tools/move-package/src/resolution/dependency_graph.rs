@@ -7,13 +7,15 @@ use petgraph::{algo, prelude::DiGraphMap, Direction};
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
     fmt,
-    io::{BufWriter, Read, Write},
+    fs::File,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
 use crate::{
     package_hooks,
     source_package::{
+        layout::SourcePackageLayout,
         manifest_parser::parse_dependency,
         parsed_manifest::{
             CustomDepInfo, Dependency, DependencyKind, GitInfo, NamedAddress, PackageName,
@@ -23,8 +25,9 @@ use crate::{
 };
 
 use super::{
-    download_and_update_if_remote,
-    lock_file::{schema, LockFile},
+    digest::compute_source_digest,
+    download_and_update_if_remote, local_path,
+    lock_file::{schema, LockFile, LOCK_FILE_NAME},
     parse_package_manifest,
 };
 
@@ -69,6 +72,10 @@ enum DependencyMode {
     DevOnly,
 }
 
+/// Sources that packages are pinned to by the lock files of the packages depending on them,
+/// relative to the root package.
+type DependencyPins = BTreeMap<PackageName, DependencyKind>;
+
 /// Wrapper struct to display a dependency as an inline table in the lock file (matching the
 /// convention in the source manifest).  This is necessary becase the `toml` crate does not
 /// currently support serializing types as inline tables.
@@ -81,12 +88,17 @@ impl DependencyGraph {
     /// `skip_fetch_latest_git_deps` controls whether package resolution will fetch the latest
     /// versions of remote dependencies, even if a version already exists locally.
     ///
+    /// `respect_dep_locks` controls whether the transitive dependencies of a dependency with a lock
+    /// file in its package are sourced from that lock file, rather than the manifests that declare
+    /// them.
+    ///
     /// `progress_output` is an output stream that is written to while generating the graph, to
     /// provide human-readable progress updates.
     pub fn new<Progress: Write>(
         root_package: &SourceManifest,
         root_path: PathBuf,
         skip_fetch_latest_git_deps: bool,
+        respect_dep_locks: bool,
         progress_output: &mut Progress,
     ) -> Result<DependencyGraph> {
         let mut graph = DependencyGraph {
@@ -101,6 +113,7 @@ impl DependencyGraph {
             .extend_graph(
                 DependencyKind::default(),
                 root_package,
+                respect_dep_locks.then(DependencyPins::new).as_ref(),
                 skip_fetch_latest_git_deps,
                 progress_output,
             )
@@ -144,15 +157,39 @@ impl DependencyGraph {
             package_graph.add_edge(root, *dep, DependencyMode::DevOnly);
         }
 
+        let (locked, compiler_version) = schema::Dependencies::read(lock)?;
+        if let Some(version) = compiler_version {
+            if version != schema::COMPILER_VERSION {
+                eprintln!(
+                    "Warning: lock file was written by version {} of the compiler, but this is \
+                     version {}, its dependencies may need to be resolved again",
+                    version,
+                    schema::COMPILER_VERSION,
+                );
+            }
+        }
+
         // Fill in the remaining dependencies, and the package source information from the lock
         // file.
-        for schema::Dependency {
-            name,
-            source,
+        let schema::DependencyEdges {
             dependencies,
             dev_dependencies,
-        } in schema::Dependencies::read(lock)?
-        {
+        } = schema::Dependencies::partition(&locked);
+
+        for (edges, mode) in [
+            (dependencies, DependencyMode::Always),
+            (dev_dependencies, DependencyMode::DevOnly),
+        ] {
+            for (package, deps) in edges {
+                let package = PackageName::from(package.as_str());
+                for dep in deps {
+                    let dep = PackageName::from(dep.as_str());
+                    package_graph.add_edge(package, dep, mode);
+                }
+            }
+        }
+
+        for schema::Dependency { name, source, .. } in locked {
             let package = PackageName::from(name.as_str());
             let source = parse_dependency(package.as_str(), source)
                 .with_context(|| format!("Deserializing dependency {}", package))?;
@@ -170,16 +207,6 @@ impl DependencyGraph {
                     );
                 }
             };
-
-            for dep in dependencies.iter().flatten() {
-                let dep = PackageName::from(dep.as_str());
-                package_graph.add_edge(package, dep, DependencyMode::Always);
-            }
-
-            for dep in dev_dependencies.iter().flatten() {
-                let dep = PackageName::from(dep.as_str());
-                package_graph.add_edge(package, dep, DependencyMode::DevOnly);
-            }
         }
 
         let mut graph = DependencyGraph {
@@ -196,21 +223,24 @@ impl DependencyGraph {
         Ok(graph)
     }
 
+    /// The source of every package in the graph (excluding the root package), relative to the root
+    /// package.
+    pub fn package_sources(&self) -> BTreeMap<PackageName, DependencyKind> {
+        self.package_table
+            .iter()
+            .map(|(pkg, dep)| (*pkg, dep.kind.clone()))
+            .collect()
+    }
+
     /// Serialize this dependency graph into a lock file, consuming it in the process.
     ///
     /// This operation fails, writing nothing, if the graph contains a cycle, and can fail with an
     /// undefined output if it cannot be represented in a TOML file.
     pub fn write_to_lock(self, lock: &mut LockFile) -> Result<()> {
-        let mut writer = BufWriter::new(&**lock);
-        for (pkg, dep) in self.package_table {
-            writeln!(writer, "\n[[move.dependency]]")?;
-
-            writeln!(writer, "name = {}", str_escape(pkg.as_str())?)?;
-            writeln!(writer, "source = {}", DependencyTOML(&dep))?;
-
+        for (pkg, dep) in &self.package_table {
             let mut deps: Vec<_> = self
                 .package_graph
-                .edges(pkg)
+                .edges(*pkg)
                 .map(|(_, dep, kind)| (*kind, dep))
                 .collect();
 
@@ -218,35 +248,82 @@ impl DependencyGraph {
             // stable.
             deps.sort();
 
-            let mut deps = deps.into_iter().peekable();
-            if let Some((DependencyMode::Always, _)) = deps.peek() {
-                writeln!(writer, "dependencies = [")?;
-                while let Some((DependencyMode::Always, dep)) = deps.peek() {
-                    writeln!(writer, "  {},", str_escape(dep.as_str())?)?;
-                    deps.next();
-                }
-                writeln!(writer, "]")?;
+            let names = |mode: DependencyMode| {
+                let names: Vec<_> = deps
+                    .iter()
+                    .filter(|(kind, _)| *kind == mode)
+                    .map(|(_, dep)| dep.as_str().to_string())
+                    .collect();
+                (!names.is_empty()).then_some(names)
+            };
+
+            lock.add_dependency(&schema::Dependency {
+                name: pkg.as_str().to_string(),
+                source: source_value(dep)?,
+                digest: local_digest(&self.root_path, dep)?,
+                dependencies: names(DependencyMode::Always),
+                dev_dependencies: names(DependencyMode::DevOnly),
+            });
+        }
+
+        lock.finalize()
+    }
+
+    /// All the paths in the graph from the root package to `package` (both ends included), found by
+    /// walking dependency edges backwards from `package`.  Paths that go through a dev-dependency
+    /// of the root package are only included if `include_dev` is set, and paths that go through
+    /// dev-dependencies of other packages are never included, as those are not built for the root
+    /// package.  Returns `None` if `package` is not in the graph.
+    pub fn paths_to(
+        &self,
+        package: PackageName,
+        include_dev: bool,
+    ) -> Option<Vec<Vec<PackageName>>> {
+        if !self.package_graph.contains_node(package) {
+            return None;
+        }
+
+        let mut paths = vec![];
+
+        // Partial paths, from `package` back towards the root.
+        let mut frontier = vec![vec![package]];
+        while let Some(path) = frontier.pop() {
+            // SAFETY: Partial paths are never empty.
+            let last = *path.last().unwrap();
+            if last == self.root_package {
+                paths.push(path.into_iter().rev().collect());
+                continue;
             }
 
-            if let Some((DependencyMode::DevOnly, _)) = deps.peek() {
-                writeln!(writer, "dev-dependencies = [")?;
-                while let Some((DependencyMode::DevOnly, dep)) = deps.peek() {
-                    writeln!(writer, "  {},", str_escape(dep.as_str())?)?;
-                    deps.next();
+            for dependee in self
+                .package_graph
+                .neighbors_directed(last, Direction::Incoming)
+            {
+                let mode = self.package_graph.edge_weight(dependee, last);
+                let followed = include_dev && dependee == self.root_package;
+                if mode == Some(&DependencyMode::DevOnly) && !followed {
+                    continue;
                 }
-                writeln!(writer, "]")?;
+
+                let mut path = path.clone();
+                path.push(dependee);
+                frontier.push(path);
             }
         }
 
-        writer.flush()?;
-        Ok(())
+        // Sort to keep the output stable.
+        paths.sort();
+        Some(paths)
     }
 
     /// Add the transitive dependencies and dev-dependencies from `package` to the dependency graph.
+    /// Dependencies in `pins` are sourced from their pins rather than from `package`'s manifest
+    /// (`pins` is only provided if lock files of dependencies are respected).
     fn extend_graph<Progress: Write>(
         &mut self,
         parent: DependencyKind,
         package: &SourceManifest,
+        pins: Option<&DependencyPins>,
         skip_fetch_latest_git_deps: bool,
         progress_output: &mut Progress,
     ) -> Result<()> {
@@ -254,8 +331,11 @@ impl DependencyGraph {
         for (to, dep) in &package.dependencies {
             let mut dep = dep.clone();
             dep.kind.reroot(&parent)?;
+            if let Some(pinned) = pins.and_then(|pins| pins.get(to)) {
+                dep.kind = pinned.clone();
+            }
 
-            self.process_dependency(dep, *to, skip_fetch_latest_git_deps, progress_output)?;
+            self.process_dependency(dep, *to, pins, skip_fetch_latest_git_deps, progress_output)?;
 
             self.package_graph
                 .add_edge(from, *to, DependencyMode::Always);
@@ -264,8 +344,11 @@ impl DependencyGraph {
         for (to, dep) in &package.dev_dependencies {
             let mut dep = dep.clone();
             dep.kind.reroot(&parent)?;
+            if let Some(pinned) = pins.and_then(|pins| pins.get(to)) {
+                dep.kind = pinned.clone();
+            }
 
-            self.process_dependency(dep, *to, skip_fetch_latest_git_deps, progress_output)?;
+            self.process_dependency(dep, *to, pins, skip_fetch_latest_git_deps, progress_output)?;
 
             self.package_graph
                 .add_edge(from, *to, DependencyMode::DevOnly);
@@ -278,10 +361,14 @@ impl DependencyGraph {
     /// graph, all sourced from their respective `dep`endencies.  Fails if any of the packages in
     /// the dependency sub-graph rooted at `dep_name` are already present in `self` but sourced from
     /// a different dependency.
+    ///
+    /// If `pins` is provided, the pins in `dep_name`'s own lock file (if it has one) are added to
+    /// them for its transitive dependencies, failing if the two disagree.
     fn process_dependency<Progress: Write>(
         &mut self,
         dep: Dependency,
         dep_name: PackageName,
+        pins: Option<&DependencyPins>,
         skip_fetch_latest_git_deps: bool,
         progress_output: &mut Progress,
     ) -> Result<()> {
@@ -307,8 +394,9 @@ impl DependencyGraph {
         download_and_update_if_remote(dep_name, dep, skip_fetch_latest_git_deps, progress_output)
             .with_context(|| format!("Fetching '{}'", dep_name))?;
 
-        let (manifest, _) = parse_package_manifest(dep, &dep_name, self.root_path.clone())
-            .with_context(|| format!("Parsing manifest for '{}'", dep_name))?;
+        let (manifest, dep_path) =
+            parse_package_manifest(dep, &dep_name, self.root_path.clone())
+                .with_context(|| format!("Parsing manifest for '{}'", dep_name))?;
 
         if dep_name != manifest.package.name {
             bail!(
@@ -320,8 +408,18 @@ impl DependencyGraph {
         }
 
         let kind = dep.kind.clone();
-        self.extend_graph(kind, &manifest, skip_fetch_latest_git_deps, progress_output)
-            .with_context(|| format!("Resolving dependencies for package '{}'", dep_name))
+        let pins = pins
+            .map(|pins| with_lock_pins(pins, dep_name, &kind, &dep_path))
+            .transpose()?;
+
+        self.extend_graph(
+            kind,
+            &manifest,
+            pins.as_ref(),
+            skip_fetch_latest_git_deps,
+            progress_output,
+        )
+        .with_context(|| format!("Resolving dependencies for package '{}'", dep_name))
     }
 
     /// Check that every dependency in the graph, excluding the root package, is present in the
@@ -506,11 +604,103 @@ impl<'a> fmt::Display for SubstTOML<'a> {
     }
 }
 
+/// Extend `pins` with the sources pinned by the lock file in the package of dependency `dep_name`
+/// (sourced from `dep_kind` and found locally at `dep_path`), if it has one.  Fails if the lock
+/// file pins a package that is already pinned to a different source.
+fn with_lock_pins(
+    pins: &DependencyPins,
+    dep_name: PackageName,
+    dep_kind: &DependencyKind,
+    dep_path: &Path,
+) -> Result<DependencyPins> {
+    let mut pins = pins.clone();
+
+    let lock_path = dep_path.join(LOCK_FILE_NAME);
+    if !lock_path.is_file() {
+        return Ok(pins);
+    }
+
+    let locked = File::open(&lock_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut lock| Ok(schema::Dependencies::read(&mut lock)?.0))
+        .with_context(|| format!("Reading lock file of '{}'", dep_name))?;
+
+    for schema::Dependency { name, source, .. } in locked {
+        let package = PackageName::from(name.as_str());
+        let mut pinned = parse_dependency(package.as_str(), source).with_context(|| {
+            format!(
+                "Deserializing dependency {} pinned by '{}'",
+                package, dep_name
+            )
+        })?;
+
+        // Sources in the lock file are relative to the package it belongs to.
+        pinned.kind.reroot(dep_kind)?;
+
+        match pins.entry(package) {
+            Entry::Vacant(entry) => {
+                entry.insert(pinned.kind);
+            }
+
+            Entry::Occupied(entry) if *entry.get() == pinned.kind => {}
+
+            Entry::Occupied(entry) => {
+                let existing = Dependency {
+                    kind: entry.get().clone(),
+                    subst: None,
+                    version: None,
+                    digest: None,
+                };
+
+                bail!(
+                    "Conflicting pins found in lock file of '{0}':\n{1} = {2}\n{1} = {3}\n",
+                    dep_name,
+                    package,
+                    DependencyTOML(&existing),
+                    DependencyTOML(&pinned),
+                );
+            }
+        }
+    }
+
+    Ok(pins)
+}
+
 /// Escape a string to output in a TOML file.
 fn str_escape(s: &str) -> Result<String, fmt::Error> {
     toml::to_string(s).map_err(|_| fmt::Error)
 }
 
+/// The source of `dep` as a TOML value, the way it is recorded in lock files.
+fn source_value(dep: &Dependency) -> Result<toml::Value> {
+    let mut entry = String::new();
+    fmt::write(&mut entry, format_args!("source = {}", DependencyTOML(dep)))
+        .context("Serializing dependency source")?;
+    let mut table: toml::value::Table =
+        toml::from_str(&entry).context("Serializing dependency source")?;
+    table
+        .remove("source")
+        .context("Serializing dependency source")
+}
+
+/// The digest of the sources of `dep`, relative to the package at `root_path`, if it is a local
+/// dependency whose sources are available.
+fn local_digest(root_path: &Path, dep: &Dependency) -> Result<Option<String>> {
+    let DependencyKind::Local(_) = &dep.kind else {
+        return Ok(None);
+    };
+
+    let dep_path = root_path.join(local_path(&dep.kind));
+    if !dep_path
+        .join(SourcePackageLayout::Manifest.path())
+        .is_file()
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(compute_source_digest(&dep_path)?.to_string()))
+}
+
 /// Escape a path to output in a TOML file.
 fn path_escape(p: &Path) -> Result<String, fmt::Error> {
     str_escape(p.to_str().ok_or(fmt::Error)?)
@@ -9,6 +9,16 @@ use std::path::{Path, PathBuf};
 
 use crate::source_package::{layout::SourcePackageLayout, parsed_manifest::PackageDigest};
 
+/// Digest of the sources of the package rooted at `path`, as recorded for local dependencies in lock
+/// files: covers its manifest, and the Move files in its sources and scripts directories.
+pub fn compute_source_digest(path: &Path) -> Result<PackageDigest> {
+    compute_digest(&[
+        path.join(SourcePackageLayout::Manifest.path()),
+        path.join(SourcePackageLayout::Sources.path()),
+        path.join(SourcePackageLayout::Scripts.path()),
+    ])
+}
+
 pub fn compute_digest(paths: &[PathBuf]) -> Result<PackageDigest> {
     let mut hashed_files = Vec::new();
     let mut hash = |path: &Path| {
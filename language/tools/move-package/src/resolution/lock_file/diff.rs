@@ -0,0 +1,166 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of the dependencies pinned by two lock files, independently of any package or build.
+
+use std::{collections::BTreeMap, fmt, io::Read};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use toml::value::Value;
+
+use super::schema::{self, InlineTOML};
+
+/// Differences between the dependencies recorded in an old and a new lock file.  Each list is
+/// sorted by dependency name.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct LockDiff {
+    /// Dependencies only present in the new lock file.
+    pub added: Vec<LockedDependency>,
+    /// Dependencies only present in the old lock file.
+    pub removed: Vec<LockedDependency>,
+    /// Git dependencies whose revision changed, while still being fetched from the same repository
+    /// and sub-directory.
+    pub repinned: Vec<Repin>,
+    /// Dependencies whose source changed in any other way.
+    pub changed: Vec<SourceChange>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LockedDependency {
+    pub name: String,
+    pub source: Value,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Repin {
+    pub name: String,
+    pub git: String,
+    pub old_rev: String,
+    pub new_rev: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SourceChange {
+    pub name: String,
+    pub old_source: Value,
+    pub new_source: Value,
+}
+
+impl LockDiff {
+    /// Compare the dependencies of the `old` and `new` lock files, which are read according to the
+    /// lock file schema.
+    pub fn new(old: &mut impl Read, new: &mut impl Read) -> Result<LockDiff> {
+        let old = locked_sources(old).context("Reading old lock file")?;
+        let mut new = locked_sources(new).context("Reading new lock file")?;
+
+        let mut diff = LockDiff::default();
+        for (name, old_source) in old {
+            let Some(new_source) = new.remove(&name) else {
+                diff.removed.push(LockedDependency {
+                    name,
+                    source: old_source,
+                });
+                continue;
+            };
+
+            if old_source == new_source {
+                continue;
+            }
+
+            match repin(&old_source, &new_source) {
+                Some((git, old_rev, new_rev)) => diff.repinned.push(Repin {
+                    name,
+                    git,
+                    old_rev,
+                    new_rev,
+                }),
+                None => diff.changed.push(SourceChange {
+                    name,
+                    old_source,
+                    new_source,
+                }),
+            }
+        }
+
+        diff.added = new
+            .into_iter()
+            .map(|(name, source)| LockedDependency { name, source })
+            .collect();
+
+        Ok(diff)
+    }
+
+    /// Whether both lock files pin the same dependencies to the same sources.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.repinned.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for LockDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No dependency changes");
+        }
+
+        for LockedDependency { name, source } in &self.added {
+            writeln!(f, "+ {} = {}", name, InlineTOML(source))?;
+        }
+
+        for LockedDependency { name, source } in &self.removed {
+            writeln!(f, "- {} = {}", name, InlineTOML(source))?;
+        }
+
+        for Repin {
+            name,
+            git,
+            old_rev,
+            new_rev,
+        } in &self.repinned
+        {
+            writeln!(
+                f,
+                "~ {} repinned in {}: {} -> {}",
+                name, git, old_rev, new_rev
+            )?;
+        }
+
+        for SourceChange {
+            name,
+            old_source,
+            new_source,
+        } in &self.changed
+        {
+            writeln!(f, "~ {} source changed:", name)?;
+            writeln!(f, "    - {}", InlineTOML(old_source))?;
+            writeln!(f, "    + {}", InlineTOML(new_source))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the sources of the dependencies in a lock file, indexed by dependency name.
+fn locked_sources(lock: &mut impl Read) -> Result<BTreeMap<String, Value>> {
+    Ok(schema::Dependencies::read(lock)?
+        .0
+        .into_iter()
+        .map(|dep| (dep.name, dep.source))
+        .collect())
+}
+
+/// If `old` and `new` are git sources that only differ in their revision, returns the repository
+/// and the old and new revisions.
+fn repin(old: &Value, new: &Value) -> Option<(String, String, String)> {
+    let mut old = old.as_table()?.clone();
+    let mut new = new.as_table()?.clone();
+
+    let git = old.get("git")?.as_str()?.to_string();
+    let old_rev = old.remove("rev")?.as_str()?.to_string();
+    let new_rev = new.remove("rev")?.as_str()?.to_string();
+
+    (old == new).then_some((git, old_rev, new_rev))
+}
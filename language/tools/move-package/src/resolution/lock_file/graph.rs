@@ -0,0 +1,152 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The dependency graph recorded in a lock file, independently of any package or build.
+
+use std::{collections::BTreeSet, fmt, io::Read};
+
+use anyhow::{bail, Result};
+
+use super::schema::{self, DependencyEdges};
+
+/// The packages in a lock file and the edges between them, which are guaranteed to form a DAG.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LockGraph {
+    pub packages: BTreeSet<String>,
+    pub edges: DependencyEdges,
+}
+
+impl LockGraph {
+    /// Read the dependency graph from a lock file, failing if any of its packages (transitively)
+    /// depend on themselves.
+    pub fn new(lock: &mut impl Read) -> Result<LockGraph> {
+        let (dependencies, _) = schema::Dependencies::read(lock)?;
+        let graph = LockGraph {
+            packages: dependencies.iter().map(|dep| dep.name.clone()).collect(),
+            edges: schema::Dependencies::partition(&dependencies),
+        };
+
+        if let Some(cycle) = graph.find_cycle() {
+            bail!("Dependency cycle in lock file: {}", cycle.join(" -> "));
+        }
+
+        Ok(graph)
+    }
+
+    /// The packages that no other package depends on, in name order.
+    pub fn roots(&self) -> Vec<&str> {
+        let depended_on: BTreeSet<_> = self.edges().map(|(_, to, _)| to).collect();
+        self.packages
+            .iter()
+            .map(String::as_str)
+            .filter(|pkg| !depended_on.contains(pkg))
+            .collect()
+    }
+
+    /// Render the graph in GraphViz's dot language, with dev-dependency edges dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for pkg in &self.packages {
+            dot.push_str(&format!("    {:?};\n", pkg));
+        }
+        for (from, to, dev) in self.edges() {
+            let style = if dev { " [style = dashed]" } else { "" };
+            dot.push_str(&format!("    {:?} -> {:?}{};\n", from, to, style));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Every edge in the graph, as its source, its destination, and whether it is a
+    /// dev-dependency, in order of source, kind, and destination.
+    fn edges(&self) -> impl Iterator<Item = (&str, &str, bool)> {
+        let DependencyEdges {
+            dependencies,
+            dev_dependencies,
+        } = &self.edges;
+        self.packages.iter().flat_map(move |pkg| {
+            let deps = dependencies.get(pkg).into_iter().flatten();
+            let dev_deps = dev_dependencies.get(pkg).into_iter().flatten();
+            deps.map(|dep| (dep, false))
+                .chain(dev_deps.map(|dep| (dep, true)))
+                .map(move |(dep, dev)| (pkg.as_str(), dep.as_str(), dev))
+        })
+    }
+
+    /// The packages (and their dev-dependency status) that `pkg` depends on directly.
+    fn successors(&self, pkg: &str) -> Vec<(&str, bool)> {
+        self.edges()
+            .filter(|(from, _, _)| *from == pkg)
+            .map(|(_, to, dev)| (to, dev))
+            .collect()
+    }
+
+    /// A path from some package back to itself, if there is one.
+    fn find_cycle(&self) -> Option<Vec<&str>> {
+        let mut visited = BTreeSet::new();
+        for root in &self.packages {
+            let mut path = vec![];
+            if let Some(cycle) = self.find_cycle_from(root, &mut path, &mut visited) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    /// Depth-first search for a cycle through the packages reachable from `pkg`, where `path` is
+    /// the path leading to `pkg`, and `visited` are the packages whose reachable packages are known
+    /// not to contain a cycle.
+    fn find_cycle_from<'a>(
+        &'a self,
+        pkg: &'a str,
+        path: &mut Vec<&'a str>,
+        visited: &mut BTreeSet<&'a str>,
+    ) -> Option<Vec<&'a str>> {
+        if let Some(start) = path.iter().position(|p| *p == pkg) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(pkg);
+            return Some(cycle);
+        }
+
+        if visited.contains(pkg) {
+            return None;
+        }
+
+        path.push(pkg);
+        for (dep, _) in self.successors(pkg) {
+            if let Some(cycle) = self.find_cycle_from(dep, path, visited) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+
+        visited.insert(pkg);
+        None
+    }
+
+    fn fmt_tree(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        pkg: &str,
+        dev: bool,
+        depth: usize,
+    ) -> fmt::Result {
+        let suffix = if dev { " (dev)" } else { "" };
+        writeln!(f, "{:indent$}{}{}", "", pkg, suffix, indent = 2 * depth)?;
+        for (dep, dev) in self.successors(pkg) {
+            self.fmt_tree(f, dep, dev, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints the graph as indented trees of dependencies, one per root package.  Packages depended on
+/// by several others are repeated under each of them.
+impl fmt::Display for LockGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for root in self.roots() {
+            self.fmt_tree(f, root, false, 0)?;
+        }
+        Ok(())
+    }
+}
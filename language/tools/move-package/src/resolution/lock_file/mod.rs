@@ -2,20 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+    fmt,
     fs::{self, File},
+    io::Read,
     ops::{Deref, DerefMut},
     path::Path,
 };
 
 use anyhow::{bail, Context, Result};
+use named_lock::NamedLock;
+use sha2::{Digest, Sha256};
 use tempfile::NamedTempFile;
 
+pub mod diff;
+pub mod graph;
 pub mod schema;
 
+/// Name of the lock file saved in a package's root directory.
+pub const LOCK_FILE_NAME: &str = "Move.lock";
+
 use crate::{
-    compilation::package_layout::CompiledPackageLayout, source_package::layout::SourcePackageLayout,
+    compilation::package_layout::CompiledPackageLayout,
+    source_package::{layout::SourcePackageLayout, manifest_parser::parse_dependency},
 };
 
+use super::{digest::compute_source_digest, local_path};
+
 /// Representation of a machine-generated, human-readable text file that is generated as part of the
 /// build, with the following properties:
 ///
@@ -29,10 +41,20 @@ use crate::{
 /// compiled package output directory, and it must be explicitly committed to its place in the
 /// package root on success, consuming the lock file.
 ///
-/// Lock files wrap a `File` which can be accessed by dereferencing it.
+/// Committing is safe against concurrent writers, in this or other processes: commits to the same
+/// path are serialized by an advisory lock, and each replaces the lock file with its complete
+/// contents in a single atomic rename, so readers and later writers observe either the previous
+/// lock file or a complete new one, never a mix of the two.
+///
+/// Lock files wrap a `File` which can be accessed by dereferencing it.  Dependencies can also be
+/// added to them one at a time, with `add_dependency`, to be written out together by `finalize`.
 #[derive(Debug)]
 pub struct LockFile {
     file: NamedTempFile,
+    /// Digest of the package's manifest, recorded in the lock file's header.
+    manifest_digest: String,
+    /// Dependencies added since the lock file was last finalized.
+    dependencies: Vec<schema::Dependency>,
 }
 
 impl LockFile {
@@ -58,14 +80,70 @@ impl LockFile {
             .tempfile_in(locks_dir)
             .context("Creating lock file")?;
 
-        schema::write_prologue(&mut lock).context("Initializing lock file")?;
+        let manifest_digest = manifest_digest(&toml_manifest)?;
+        schema::write_prologue(&mut lock, &manifest_digest).context("Initializing lock file")?;
+
+        Ok(LockFile {
+            file: lock,
+            manifest_digest,
+            dependencies: vec![],
+        })
+    }
+
+    /// Whether the lock file saved alongside the manifest at `manifest_path` may no longer reflect
+    /// the manifest, because the manifest changed since the lock file was written.  Lock files
+    /// that are missing, or that do not record the digest of the manifest they were written for,
+    /// are considered stale.
+    pub fn is_stale(manifest_path: &Path) -> Result<bool> {
+        let lock_path = manifest_path.with_file_name(LOCK_FILE_NAME);
+        if !lock_path.is_file() {
+            return Ok(true);
+        }
+
+        let mut lock = File::open(&lock_path).context("Opening lock file")?;
+        let Some(recorded) = schema::read_manifest_digest(&mut lock)? else {
+            return Ok(true);
+        };
+
+        Ok(recorded != manifest_digest(manifest_path)?)
+    }
 
-        Ok(LockFile { file: lock })
+    /// Add `dep` to the dependencies to write to the lock file when it is finalized.
+    pub fn add_dependency(&mut self, dep: &schema::Dependency) {
+        self.dependencies.push(dep.clone());
+    }
+
+    /// Write the dependencies added since the last call as `[[move.dependency]]` tables, sorted by
+    /// name so that the output is deterministic, and record the checksum of the lock file's
+    /// contents in its header.  NOTE: Added dependencies are only written to the lock file (and
+    /// saved by `commit`) once it is finalized.
+    pub fn finalize(&mut self) -> Result<()> {
+        let mut dependencies = std::mem::take(&mut self.dependencies);
+        dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+        schema::write_dependencies(self, &dependencies)?;
+        schema::write_checksum(self)
+    }
+
+    /// Reads the contents written to the lock file so far.
+    pub fn read_contents(&self) -> Result<String> {
+        fs::read_to_string(self.file.path()).context("Reading lock file")
     }
 
     /// Consume the lock file, moving it to its final position at `lock_path`.  NOTE: If this
     /// function is not called, the contents of the lock file will be discarded.
     pub fn commit(self, lock_path: impl AsRef<Path>) -> Result<()> {
+        let lock_path = lock_path.as_ref();
+        let commit_lock = NamedLock::create(&commit_lock_name(lock_path)?)
+            .context("Creating lock for committing lock file")?;
+        let _guard = commit_lock
+            .lock()
+            .context("Acquiring lock for committing lock file")?;
+
+        // Make sure all the contents are written out before the rename makes them visible.
+        self.file
+            .as_file()
+            .sync_all()
+            .context("Flushing lock file")?;
         self.file
             .persist(lock_path)
             .context("Committing lock file")?;
@@ -73,6 +151,95 @@ impl LockFile {
     }
 }
 
+/// Digest of the contents of the manifest at `manifest_path`.
+fn manifest_digest(manifest_path: &Path) -> Result<String> {
+    let contents = fs::read(manifest_path)
+        .with_context(|| format!("Reading manifest {}", manifest_path.to_string_lossy()))?;
+    Ok(format!("{:X}", Sha256::digest(&contents)))
+}
+
+/// Name of the advisory lock serializing commits to the lock file at `lock_path`, identifying it by
+/// its canonical path (it need not exist yet, but its directory must).
+pub fn commit_lock_name(lock_path: &Path) -> Result<String> {
+    let dir = match lock_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let Some(file_name) = lock_path.file_name() else {
+        bail!("Invalid lock file path: {}", lock_path.to_string_lossy());
+    };
+
+    let canonical = fs::canonicalize(dir)
+        .with_context(|| format!("Resolving lock file path {}", lock_path.to_string_lossy()))?
+        .join(file_name);
+
+    Ok(format!(
+        "move_lock_file_{:x}",
+        Sha256::digest(canonical.to_string_lossy().as_bytes())
+    ))
+}
+
+/// A dependency whose sources no longer match the digest recorded for them in a lock file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestMismatch {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Recompute the digests of the dependencies recorded in `lock`, the lock file of the package at
+/// `root_path`, returning the dependencies whose sources changed since it was written.
+/// Dependencies without a recorded digest, or whose sources are not available, are skipped.
+pub fn verify_digests(lock: &mut impl Read, root_path: &Path) -> Result<Vec<DigestMismatch>> {
+    let (dependencies, _) = schema::Dependencies::read(lock)?;
+
+    let mut mismatches = vec![];
+    for schema::Dependency {
+        name,
+        source,
+        digest,
+        ..
+    } in dependencies
+    {
+        let Some(expected) = digest else {
+            continue;
+        };
+
+        let dep = parse_dependency(&name, source)
+            .with_context(|| format!("Deserializing dependency {}", name))?;
+
+        let dep_path = root_path.join(local_path(&dep.kind));
+        if !dep_path
+            .join(SourcePackageLayout::Manifest.path())
+            .is_file()
+        {
+            continue;
+        }
+
+        let actual = compute_source_digest(&dep_path)?.to_string();
+        if actual != expected {
+            mismatches.push(DigestMismatch {
+                name,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(mismatches)
+}
+
+impl fmt::Display for DigestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sources of dependency '{}' changed since it was locked, expected digest {}, found {}",
+            self.name, self.expected, self.actual,
+        )
+    }
+}
+
 impl Deref for LockFile {
     type Target = File;
 
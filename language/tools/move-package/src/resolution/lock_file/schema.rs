@@ -2,16 +2,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Serde compatible types to deserialize the schematized parts of the lock file (everything in the
-//! [move] table).  This module does not support serialization because of limitations in the `toml`
-//! crate related to serializing types as inline tables.
+//! [move] table).  Dependencies are serialized by hand rather than through serde, because of
+//! limitations in the `toml` crate related to serializing types as inline tables.
 
-use std::io::{Read, Write};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Write as _},
+    io::{Read, Seek, SeekFrom, Write},
+};
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tempfile::NamedTempFile;
 use toml::value::Value;
 
+use super::LockFile;
+
 /// Lock file version written by this version of the compiler.  Backwards compatibility is
 /// guaranteed (the compiler can read lock files with older versions), forward compatibility is not
 /// (the compiler will fail to read lock files at newer versions).
@@ -19,13 +26,17 @@ use toml::value::Value;
 /// TODO(amnn): Set to version 1 when stabilised.
 pub const VERSION: u64 = 0;
 
+/// Version of the compiler recorded in lock files written by it, to detect lock files resolved by a
+/// different version of the compiler.
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[derive(Deserialize)]
 pub struct Dependencies {
     #[serde(rename = "dependency")]
     dependencies: Option<Vec<Dependency>>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Dependency {
     /// The name of the dependency (corresponds to the key for the dependency in the source
     /// manifest).
@@ -35,11 +46,36 @@ pub struct Dependency {
     /// terms of serde-compatible structs, so it is deserialized into a generic data structure.
     pub source: Value,
 
+    /// Digest of the dependency's sources when the lock file was written, to detect changes to
+    /// them since.  Only recorded for local dependencies (git dependencies are pinned to a
+    /// revision), and missing from lock files written before digests were recorded.
+    pub digest: Option<String>,
+
     pub dependencies: Option<Vec<String>>,
     #[serde(rename = "dev-dependencies")]
     pub dev_dependencies: Option<Vec<String>>,
 }
 
+/// Errors reading a lock file that callers may want to handle specifically (they can be recovered
+/// from the `anyhow::Error`s returned when reading lock files by downcasting).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockError {
+    /// The lock file's format version is newer than the latest version this library supports.
+    UnsupportedVersion { found: u64, supported: u64 },
+}
+
+/// The dependency edges between the packages in a lock file, partitioned by kind.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DependencyEdges {
+    /// The dependencies of each package, needed to build it in any mode.
+    pub dependencies: BTreeMap<String, BTreeSet<String>>,
+
+    /// The dev-dependencies of each package that are not also its dependencies.  Dev-dependencies
+    /// are not transitive: they are only needed to build a package in dev mode as the root
+    /// package.
+    pub dev_dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct Schema<T> {
     #[serde(rename = "move")]
@@ -49,27 +85,63 @@ struct Schema<T> {
 #[derive(Serialize, Deserialize)]
 struct Header {
     version: u64,
+
+    /// Version of the compiler that wrote the lock file.  Lock files written before this was
+    /// recorded do not have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    compiler_version: Option<String>,
+
+    /// Digest of the package's manifest when the lock file was written, to detect changes to the
+    /// manifest that the lock file does not reflect.  Lock files written before this was recorded
+    /// do not have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    manifest_digest: Option<String>,
+
+    /// Checksum of the contents following the header, written by the toolchain to detect edits made
+    /// to the lock file by hand.  Lock files without a checksum are not verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+}
+
+impl Header {
+    /// The header of lock files written by this version of the compiler.
+    fn current(manifest_digest: Option<String>, checksum: Option<String>) -> Header {
+        Header {
+            version: VERSION,
+            compiler_version: Some(COMPILER_VERSION.to_string()),
+            manifest_digest,
+            checksum,
+        }
+    }
 }
 
 impl Dependencies {
     /// Read dependencies from the lock file, assuming the file's format matches the schema expected
     /// by this lock file, and its version is not newer than the version supported by this library.
-    pub fn read(lock: &mut impl Read) -> Result<Vec<Dependency>> {
+    /// Fails if a dependency refers to a package that has no entry in the lock file.
+    /// Also returns the version of the compiler that wrote the lock file, if it was recorded.
+    pub fn read(lock: &mut impl Read) -> Result<(Vec<Dependency>, Option<String>)> {
         let contents = {
             let mut buf = String::new();
             lock.read_to_string(&mut buf).context("Reading lock file")?;
             buf
         };
 
-        let Schema {
-            move_: Header { version },
-        } = toml::de::from_str::<Schema<Header>>(&contents).context("Deserializing lock header")?;
-
-        if version > VERSION {
-            bail!(
-                "Lock file format is too new, expected version {} or below, found {}",
-                VERSION,
-                version
+        let Schema { move_: header } =
+            toml::de::from_str::<Schema<Header>>(&contents).context("Deserializing lock header")?;
+
+        if header.version > VERSION {
+            return Err(LockError::UnsupportedVersion {
+                found: header.version,
+                supported: VERSION,
+            }
+            .into());
+        }
+
+        if !checksum_matches(&header, &contents)? {
+            eprintln!(
+                "Warning: lock file dependencies do not match their checksum, the lock file may \
+                 have been edited manually"
             );
         }
 
@@ -78,22 +150,278 @@ impl Dependencies {
         } = toml::de::from_str::<Schema<Dependencies>>(&contents)
             .context("Deserializing dependencies")?;
 
-        Ok(dependencies.unwrap_or_default())
+        let dependencies = dependencies.unwrap_or_default();
+        check_references(&dependencies)?;
+
+        Ok((dependencies, header.compiler_version))
+    }
+
+    /// Partition the dependency edges of `dependencies`, as read from a lock file, by kind.  Packages
+    /// are only included for the kinds of edges they have.
+    pub fn partition(dependencies: &[Dependency]) -> DependencyEdges {
+        let mut edges = DependencyEdges::default();
+        for dep in dependencies {
+            let always: BTreeSet<_> = dep.dependencies.iter().flatten().cloned().collect();
+            let dev_only: BTreeSet<_> = dep
+                .dev_dependencies
+                .iter()
+                .flatten()
+                .filter(|name| !always.contains(*name))
+                .cloned()
+                .collect();
+
+            if !always.is_empty() {
+                edges
+                    .dependencies
+                    .entry(dep.name.clone())
+                    .or_default()
+                    .extend(always);
+            }
+
+            if !dev_only.is_empty() {
+                edges
+                    .dev_dependencies
+                    .entry(dep.name.clone())
+                    .or_default()
+                    .extend(dev_only);
+            }
+        }
+
+        edges
+    }
+
+    /// The dependencies to write to a lock file, in the order they should be written in.
+    pub fn new(dependencies: Vec<Dependency>) -> Dependencies {
+        Dependencies {
+            dependencies: Some(dependencies),
+        }
+    }
+
+    /// Write these dependencies as a lock file that can be read back by `Dependencies::read`,
+    /// starting with the same prologue (including a checksum of the dependencies) as lock files
+    /// written during resolution.
+    pub fn write(&self, out: &mut impl Write) -> Result<()> {
+        let mut dependencies = String::new();
+        for dep in self.dependencies.iter().flatten() {
+            write_dependency(&mut dependencies, dep)?;
+        }
+
+        let prologue = prologue(&Header::current(None, Some(checksum(&dependencies))))?;
+        write!(out, "{}{}", prologue, dependencies)?;
+        Ok(())
     }
 }
 
-/// Write the initial part of the lock file.
-pub(crate) fn write_prologue(file: &mut NamedTempFile) -> Result<()> {
+/// Check that every package that `dependencies` depend on (including as a dev-dependency) has an
+/// entry of its own among them.
+fn check_references(dependencies: &[Dependency]) -> Result<()> {
+    let names: BTreeSet<_> = dependencies.iter().map(|dep| dep.name.as_str()).collect();
+    for dep in dependencies {
+        let referenced = dep
+            .dependencies
+            .iter()
+            .chain(&dep.dev_dependencies)
+            .flatten();
+        for name in referenced {
+            if !names.contains(name.as_str()) {
+                bail!(
+                    "Dependency '{}' of '{}' is missing from the lock file",
+                    name,
+                    dep.name
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Append `dependencies` to `lock` as `[[move.dependency]]` tables, in order.
+pub(crate) fn write_dependencies(lock: &mut LockFile, dependencies: &[Dependency]) -> Result<()> {
+    let mut out = String::new();
+    for dep in dependencies {
+        write_dependency(&mut out, dep)?;
+    }
+
+    lock.seek(SeekFrom::End(0))?;
+    write!(lock, "{}", out)?;
+    Ok(())
+}
+
+/// Write `dep` as a `[[move.dependency]]` table, formatting its source as an inline table.
+fn write_dependency(out: &mut String, dep: &Dependency) -> fmt::Result {
+    writeln!(out, "\n[[move.dependency]]")?;
     writeln!(
-        file,
-        "# @generated by Move, please check-in and do not edit manually.\n"
+        out,
+        "name = {}",
+        InlineTOML(&Value::String(dep.name.clone()))
     )?;
+    writeln!(out, "source = {}", InlineTOML(&dep.source))?;
+    if let Some(digest) = &dep.digest {
+        writeln!(
+            out,
+            "digest = {}",
+            InlineTOML(&Value::String(digest.clone()))
+        )?;
+    }
 
-    let prologue = toml::ser::to_string(&Schema {
-        move_: Header { version: VERSION },
-    })?;
+    for (key, deps) in [
+        ("dependencies", &dep.dependencies),
+        ("dev-dependencies", &dep.dev_dependencies),
+    ] {
+        let Some(deps) = deps else {
+            continue;
+        };
+
+        writeln!(out, "{} = [", key)?;
+        for dep in deps {
+            writeln!(out, "  {},", InlineTOML(&Value::String(dep.clone())))?;
+        }
+        writeln!(out, "]")?;
+    }
+
+    Ok(())
+}
+
+/// Check whether the contents of a lock file following its header match the checksum recorded
+/// in the header.  Lock files without a checksum trivially match.
+pub fn verify_checksum(lock: &mut impl Read) -> Result<bool> {
+    let mut contents = String::new();
+    lock.read_to_string(&mut contents)
+        .context("Reading lock file")?;
+
+    let Schema { move_: header } =
+        toml::de::from_str::<Schema<Header>>(&contents).context("Deserializing lock header")?;
 
-    write!(file, "{}", prologue)?;
+    checksum_matches(&header, &contents)
+}
 
+/// Write the initial part of the lock file, for a package whose manifest has `manifest_digest`.
+pub(crate) fn write_prologue(file: &mut NamedTempFile, manifest_digest: &str) -> Result<()> {
+    let header = Header::current(Some(manifest_digest.to_string()), None);
+    write!(file, "{}", prologue(&header)?)?;
     Ok(())
 }
+
+/// Read the digest of the package manifest recorded in the header of the lock file, if there is
+/// one.
+pub(crate) fn read_manifest_digest(lock: &mut impl Read) -> Result<Option<String>> {
+    let mut contents = String::new();
+    lock.read_to_string(&mut contents)
+        .context("Reading lock file")?;
+
+    let Schema { move_: header } =
+        toml::de::from_str::<Schema<Header>>(&contents).context("Deserializing lock header")?;
+
+    Ok(header.manifest_digest)
+}
+
+/// The contents of a lock file following its prologue (the comment and header written by the
+/// toolchain), i.e., its dependencies.
+pub(crate) fn strip_header(contents: &str) -> Result<&str> {
+    let Schema { move_: header } =
+        toml::de::from_str::<Schema<Header>>(contents).context("Deserializing lock header")?;
+
+    let Some(dependencies) = contents.strip_prefix(&prologue(&header)?) else {
+        bail!("Lock file does not start with its prologue");
+    };
+
+    Ok(dependencies)
+}
+
+/// Record the checksum of everything written to `lock` after its prologue in the lock file's
+/// header.
+pub(crate) fn write_checksum(lock: &mut LockFile) -> Result<()> {
+    let contents = lock.read_contents()?;
+    let dependencies = strip_header(&contents)?;
+
+    let prologue = prologue(&Header::current(
+        Some(lock.manifest_digest.clone()),
+        Some(checksum(dependencies)),
+    ))?;
+
+    lock.set_len(0)?;
+    lock.rewind()?;
+    write!(lock, "{}{}", prologue, dependencies)?;
+
+    Ok(())
+}
+
+/// The initial part of a lock file (a comment and the header), as written by the toolchain.
+fn prologue(header: &Header) -> Result<String> {
+    let header = toml::ser::to_string(&Schema { move_: header })?;
+
+    Ok(format!(
+        "# @generated by Move, please check-in and do not edit manually.\n\n{}",
+        header
+    ))
+}
+
+/// Check whether the lock file `contents` starts with the prologue written for `header`, followed
+/// by contents matching the checksum in the header (if there is one).
+fn checksum_matches(header: &Header, contents: &str) -> Result<bool> {
+    let Some(expected) = &header.checksum else {
+        return Ok(true);
+    };
+
+    let prologue = prologue(header)?;
+    Ok(match contents.strip_prefix(&prologue) {
+        Some(dependencies) => &checksum(dependencies) == expected,
+        None => false,
+    })
+}
+
+/// Checksum of the contents of a lock file following its header.
+fn checksum(dependencies: &str) -> String {
+    format!("{:X}", Sha256::digest(dependencies.as_bytes()))
+}
+
+/// Displays a TOML value on a single line, the way sources are written in lock files.
+pub(crate) struct InlineTOML<'a>(pub &'a Value);
+
+impl<'a> fmt::Display for InlineTOML<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Table(table) => {
+                let mut entries = table.iter();
+                let Some((key, value)) = entries.next() else {
+                    return f.write_str("{}");
+                };
+
+                write!(f, "{{ {} = {}", key, InlineTOML(value))?;
+                for (key, value) in entries {
+                    write!(f, ", {} = {}", key, InlineTOML(value))?;
+                }
+                f.write_str(" }")
+            }
+
+            Value::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", InlineTOML(value))?;
+                }
+                f.write_str("]")
+            }
+
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Lock file format is too new, found version {}, but only versions 0 to {} are \
+                 supported.  Upgrade to a newer version of the Move CLI to read this lock file",
+                found, supported,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LockError {}
@@ -2,7 +2,7 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use move_command_line_common::env::MOVE_HOME;
 use std::{
@@ -23,12 +23,13 @@ use crate::{
             SourceManifest,
         },
     },
-    BuildConfig,
+    timings, BuildConfig,
 };
 
 pub mod dependency_graph;
 mod digest;
 pub mod lock_file;
+pub mod policy;
 pub mod resolution_graph;
 
 pub fn download_dependency_repos<Progress: Write>(
@@ -70,6 +71,21 @@ fn parse_package_manifest(
     root_path.push(local_path(&dep.kind));
     let manifest_path = root_path.join(SourcePackageLayout::Manifest.path());
 
+    if let DependencyKind::Git(GitInfo {
+        git_url, subdir, ..
+    }) = &dep.kind
+    {
+        if !subdir.as_os_str().is_empty() && !manifest_path.is_file() {
+            bail!(
+                "Sub-directory {:?} of git repository '{}' does not contain a package manifest \
+                 for '{}'",
+                subdir,
+                git_url,
+                dep_name,
+            );
+        }
+    }
+
     let contents = fs::read_to_string(&manifest_path).with_context(|| {
         format!(
             "Unable to find package manifest for '{}' at {:?}",
@@ -89,6 +105,7 @@ fn download_and_update_if_remote<Progress: Write>(
     skip_fetch_latest_git_deps: bool,
     progress_output: &mut Progress,
 ) -> Result<()> {
+    let _timer = timings::PhaseTimer::start(timings::FETCH, Some(dep_name));
     match &dep.kind {
         DependencyKind::Local(_) => Ok(()),
 
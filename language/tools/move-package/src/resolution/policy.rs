@@ -0,0 +1,165 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Policies restricting which packages are allowed in a package's transitive dependency graph,
+//! e.g. for organizations that want to enforce them in CI.  Policies are read from TOML files
+//! listing the rules that dependencies must not match:
+//!
+//! ```toml
+//! [[deny]]
+//! name = "Unaudited"
+//!
+//! [[deny]]
+//! git = "https://github.com/untrusted-org/"
+//!
+//! [[deny]]
+//! license = "GPL-3.0"
+//! ```
+//!
+//! A dependency matches a rule if it matches all the criteria that the rule specifies.
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::source_package::{
+    manifest_parser::parse_move_manifest_from_file,
+    parsed_manifest::{DependencyKind, GitInfo, PackageName},
+};
+
+use super::{dependency_graph::DependencyGraph, local_path};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependencyPolicy {
+    /// Rules that no dependency in the graph is allowed to match.
+    #[serde(default)]
+    pub deny: Vec<DenyRule>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DenyRule {
+    /// Matches dependencies with exactly this package name.
+    pub name: Option<String>,
+    /// Matches git dependencies whose repository URL starts with this prefix.
+    pub git: Option<String>,
+    /// Matches dependencies whose manifest declares exactly this license.
+    pub license: Option<String>,
+}
+
+/// A dependency in the graph that is denied by the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub package: PackageName,
+    pub rule: DenyRule,
+}
+
+impl DependencyPolicy {
+    /// Read a policy from the TOML file at `path`.
+    pub fn read(path: &Path) -> Result<DependencyPolicy> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Reading dependency policy at {:?}", path))?;
+        let policy: DependencyPolicy = toml::from_str(&contents)
+            .with_context(|| format!("Parsing dependency policy at {:?}", path))?;
+
+        for rule in &policy.deny {
+            if rule.name.is_none() && rule.git.is_none() && rule.license.is_none() {
+                bail!(
+                    "Rule in dependency policy at {:?} does not specify a 'name', 'git' or \
+                     'license' to match",
+                    path,
+                );
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Check every dependency in `graph`, for the package at `root_path`, against this policy,
+    /// returning the dependencies that are denied, and the first rule denying each of them, in
+    /// order of package name.  Dependencies are expected to have already been fetched, in order to
+    /// read their licenses.
+    pub fn check(&self, graph: &DependencyGraph, root_path: &Path) -> Result<Vec<Violation>> {
+        let mut violations = vec![];
+        for (package, kind) in graph.package_sources() {
+            let license = if self.deny.iter().any(|rule| rule.license.is_some()) {
+                let manifest = parse_move_manifest_from_file(&root_path.join(local_path(&kind)))
+                    .with_context(|| format!("Reading manifest for '{}'", package))?;
+                manifest
+                    .package
+                    .license
+                    .map(|license| license.as_str().trim_matches('"').to_string())
+            } else {
+                None
+            };
+
+            if let Some(rule) = self
+                .deny
+                .iter()
+                .find(|rule| rule.matches(package, &kind, license.as_deref()))
+            {
+                violations.push(Violation {
+                    package,
+                    rule: rule.clone(),
+                });
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+impl DenyRule {
+    fn matches(&self, package: PackageName, kind: &DependencyKind, license: Option<&str>) -> bool {
+        if let Some(name) = &self.name {
+            if name != package.as_str() {
+                return false;
+            }
+        }
+
+        if let Some(prefix) = &self.git {
+            match kind {
+                DependencyKind::Git(GitInfo { git_url, .. })
+                    if git_url.as_str().starts_with(prefix.as_str()) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(denied) = &self.license {
+            if license != Some(denied.as_str()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for DenyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let criteria: Vec<_> = [
+            self.name.as_ref().map(|name| format!("name = {:?}", name)),
+            self.git.as_ref().map(|git| format!("git = {:?}", git)),
+            self.license
+                .as_ref()
+                .map(|license| format!("license = {:?}", license)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        write!(f, "{{ {} }}", criteria.join(", "))
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Dependency '{}' is denied by rule {}",
+            self.package, self.rule
+        )
+    }
+}
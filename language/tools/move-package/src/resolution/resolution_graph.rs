@@ -7,8 +7,8 @@ use crate::{
     source_package::{
         layout::SourcePackageLayout,
         parsed_manifest::{
-            Dependency, FileName, NamedAddress, PackageDigest, PackageName, SourceManifest,
-            SubstOrRename,
+            Dependency, DependencyKind, FileName, NamedAddress, PackageDigest, PackageName,
+            SourceManifest, SubstOrRename,
         },
     },
     BuildConfig,
@@ -94,9 +94,27 @@ pub struct ResolutionPackage<T> {
 
 impl ResolvingGraph {
     pub fn new<Progress: Write>(
+        root_package: SourceManifest,
+        root_package_path: PathBuf,
+        build_options: BuildConfig,
+        progress_output: &mut Progress,
+    ) -> Result<ResolvingGraph> {
+        Self::with_dependency_pins(
+            root_package,
+            root_package_path,
+            build_options,
+            BTreeMap::new(),
+            progress_output,
+        )
+    }
+
+    /// Like `new`, but packages in `dependency_pins` are fetched from the pinned source (relative
+    /// to the root package) instead of the source declared by the packages depending on them.
+    pub fn with_dependency_pins<Progress: Write>(
         root_package: SourceManifest,
         root_package_path: PathBuf,
         mut build_options: BuildConfig,
+        dependency_pins: BTreeMap<PackageName, DependencyKind>,
         progress_output: &mut Progress,
     ) -> Result<ResolvingGraph> {
         if build_options.architecture.is_none() {
@@ -117,6 +135,7 @@ impl ResolvingGraph {
                 root_package.clone(),
                 root_package_path,
                 true,
+                &dependency_pins,
                 progress_output,
             )
             .with_context(|| {
@@ -137,6 +156,16 @@ impl ResolvingGraph {
             package_table,
         } = self;
 
+        // Packages that leave each named address unassigned (as "_") in their manifest
+        let mut unassigned_by: BTreeMap<NamedAddress, Vec<PackageName>> = BTreeMap::new();
+        for (name, package) in &package_table {
+            for (addr_name, value) in package.source_package.addresses.iter().flatten() {
+                if value.is_none() {
+                    unassigned_by.entry(*addr_name).or_default().push(*name);
+                }
+            }
+        }
+
         let mut unresolved_addresses = Vec::new();
 
         let resolved_package_table = package_table
@@ -156,9 +185,10 @@ impl ResolvingGraph {
                     .filter_map(|(addr_name, instantiation_opt)| {
                         match *instantiation_opt.value.borrow() {
                             None => {
-                                unresolved_addresses.push(format!(
-                                    "Named address '{}' in package '{}'",
-                                    addr_name, name
+                                unresolved_addresses.push(unresolved_address_message(
+                                    addr_name,
+                                    name,
+                                    &unassigned_by,
                                 ));
                                 None
                             }
@@ -203,6 +233,7 @@ impl ResolvingGraph {
         package: SourceManifest,
         package_path: PathBuf,
         is_root_package: bool,
+        dependency_pins: &BTreeMap<PackageName, DependencyKind>,
         progress_output: &mut Progress,
     ) -> Result<()> {
         let package_name = package.package.name;
@@ -256,7 +287,13 @@ impl ResolvingGraph {
             self.graph.add_edge(package_node_id, dep_node_id, ());
 
             let (dep_renaming, dep_resolution_table) = self
-                .process_dependency(dep_name, dep, package_path.clone(), progress_output)
+                .process_dependency(
+                    dep_name,
+                    dep,
+                    package_path.clone(),
+                    dependency_pins,
+                    progress_output,
+                )
                 .with_context(|| {
                     format!(
                         "While resolving dependency '{}' in package '{}'",
@@ -395,10 +432,16 @@ impl ResolvingGraph {
     fn process_dependency<Progress: Write>(
         &mut self,
         dep_name_in_pkg: PackageName,
-        dep: Dependency,
-        root_path: PathBuf,
+        mut dep: Dependency,
+        mut root_path: PathBuf,
+        dependency_pins: &BTreeMap<PackageName, DependencyKind>,
         progress_output: &mut Progress,
     ) -> Result<(Renaming, ResolvingTable)> {
+        if let Some(pinned) = dependency_pins.get(&dep_name_in_pkg) {
+            dep.kind = pinned.clone();
+            root_path = self.root_package_path.clone();
+        }
+
         download_and_update_if_remote(
             dep_name_in_pkg,
             &dep,
@@ -408,10 +451,14 @@ impl ResolvingGraph {
         let (dep_package, dep_package_dir) =
             parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
-        self.build_resolution_graph(dep_package.clone(), dep_package_dir, false, progress_output)
-            .with_context(|| {
-                format!("Unable to resolve package dependency '{}'", dep_name_in_pkg)
-            })?;
+        self.build_resolution_graph(
+            dep_package.clone(),
+            dep_package_dir,
+            false,
+            dependency_pins,
+            progress_output,
+        )
+        .with_context(|| format!("Unable to resolve package dependency '{}'", dep_name_in_pkg))?;
 
         if dep_name_in_pkg != dep_package.package.name {
             bail!("Name of dependency declared in package '{}' does not match dependency's package name '{}'",
@@ -611,6 +658,27 @@ impl ResolvingPackage {
     }
 }
 
+/// Describes a named address left unresolved in `package`, pointing at the dependencies that left
+/// it unassigned unless `package` did so itself.
+fn unresolved_address_message(
+    addr_name: NamedAddress,
+    package: PackageName,
+    unassigned_by: &BTreeMap<NamedAddress, Vec<PackageName>>,
+) -> String {
+    let message = format!("Named address '{}' in package '{}'", addr_name, package);
+    match unassigned_by.get(&addr_name) {
+        Some(declaring) if !declaring.contains(&package) => {
+            let declaring: Vec<_> = declaring.iter().map(|pkg| format!("'{}'", pkg)).collect();
+            format!(
+                "{} (left unassigned by dependency {})",
+                message,
+                declaring.join(", ")
+            )
+        }
+        _ => message,
+    }
+}
+
 impl ResolvingNamedAddress {
     pub fn new(address_opt: Option<AccountAddress>) -> Self {
         Self {
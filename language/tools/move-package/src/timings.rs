@@ -0,0 +1,122 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Process-wide, opt-in recording of the wall-clock time spent in each phase of building a package
+//! (parsing manifests, building the dependency graph, fetching each dependency, etc.), to help
+//! diagnose slow builds.  Recording has no effect on the build itself.
+
+use move_symbol_pool::Symbol;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{fmt, sync::Mutex, time::Instant};
+
+pub const PARSE_MANIFEST: &str = "parse manifest";
+pub const DEPENDENCY_GRAPH: &str = "dependency graph";
+pub const FETCH: &str = "fetch";
+pub const WRITE_LOCK: &str = "write lock";
+pub const RESOLVE: &str = "resolve";
+pub const COMPILE: &str = "compile";
+
+static TIMINGS: Lazy<Mutex<Option<Timings>>> = Lazy::new(|| Mutex::new(None));
+
+/// Timings recorded between calls to `start_recording` and `finish_recording`, in the order the
+/// phases finished in.
+#[derive(Debug, Clone, Serialize)]
+pub struct Timings {
+    pub entries: Vec<TimingEntry>,
+    #[serde(skip)]
+    started_at: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingEntry {
+    /// The phase of the build (one of the constants in this module).
+    pub phase: &'static str,
+    /// The package the phase was performed for, if it is specific to one package.
+    pub package: Option<String>,
+    /// Start of the phase, in seconds since recording started.
+    pub start: f64,
+    /// Time spent in the phase, in seconds.
+    pub duration: f64,
+}
+
+/// Times a phase of the build from its creation until it is dropped, and adds it to the recorded
+/// timings (if timings are being recorded).
+pub(crate) struct PhaseTimer {
+    phase: &'static str,
+    package: Option<Symbol>,
+    started_at: Instant,
+}
+
+/// Start recording timings for this process, discarding any timings recorded so far.
+pub fn start_recording() {
+    *TIMINGS.lock().unwrap() = Some(Timings {
+        entries: vec![],
+        started_at: Instant::now(),
+    });
+}
+
+/// Stop recording timings, returning the timings recorded since the call to `start_recording` (if
+/// any).
+pub fn finish_recording() -> Option<Timings> {
+    TIMINGS.lock().unwrap().take()
+}
+
+/// Run `f`, timing it as `phase` of the build (for `package`, if specified).
+pub(crate) fn time<T>(phase: &'static str, package: Option<Symbol>, f: impl FnOnce() -> T) -> T {
+    let _timer = PhaseTimer::start(phase, package);
+    f()
+}
+
+impl PhaseTimer {
+    pub(crate) fn start(phase: &'static str, package: Option<Symbol>) -> Self {
+        PhaseTimer {
+            phase,
+            package,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        // Don't panic while unwinding if a panic poisoned the lock.
+        let Ok(mut timings) = TIMINGS.lock() else {
+            return;
+        };
+
+        let Some(timings) = timings.as_mut() else {
+            return;
+        };
+
+        let start = self
+            .started_at
+            .saturating_duration_since(timings.started_at);
+
+        timings.entries.push(TimingEntry {
+            phase: self.phase,
+            package: self.package.map(|package| package.to_string()),
+            start: start.as_secs_f64(),
+            duration: self.started_at.elapsed().as_secs_f64(),
+        });
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for TimingEntry {
+            phase,
+            package,
+            start,
+            duration,
+        } in &self.entries
+        {
+            let phase = match package {
+                Some(package) => format!("{} {}", phase, package),
+                None => phase.to_string(),
+            };
+            writeln!(f, "{:>9.3}s {:>9.3}s  {}", start, duration, phase)?;
+        }
+        Ok(())
+    }
+}
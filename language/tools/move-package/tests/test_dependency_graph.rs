@@ -4,12 +4,15 @@
 use std::{
     collections::BTreeSet,
     fs::{self, File},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use move_package::{
     resolution::{dependency_graph::DependencyGraph, lock_file::LockFile},
-    source_package::manifest_parser::parse_move_manifest_from_file,
+    source_package::{
+        manifest_parser::parse_move_manifest_from_file, parsed_manifest::DependencyKind,
+    },
+    BuildConfig,
 };
 use move_symbol_pool::Symbol;
 
@@ -80,6 +83,7 @@ fn always_deps() {
         &manifest,
         pkg,
         /* skip_fetch_latest_git_deps */ true,
+        /* respect_dep_locks */ false,
         &mut std::io::sink(),
     )
     .expect("Creating DependencyGraph");
@@ -119,14 +123,276 @@ fn always_deps_from_lock() {
     );
 }
 
+#[test]
+fn paths_to_diamond() {
+    let pkg = diamond_test_package();
+    let snapshot = pkg.join("Move.locked");
+
+    let manifest = parse_move_manifest_from_file(&pkg).expect("Loading manifest");
+    let graph = DependencyGraph::read_from_lock(
+        pkg,
+        manifest,
+        &mut File::open(&snapshot).expect("Opening snapshot"),
+    )
+    .expect("Creating DependencyGraph");
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("C"), /* include_dev */ false),
+        Some(vec![
+            vec![Symbol::from("Root"), Symbol::from("A"), Symbol::from("C")],
+            vec![Symbol::from("Root"), Symbol::from("B"), Symbol::from("C")],
+        ]),
+    );
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("A"), /* include_dev */ false),
+        Some(vec![vec![Symbol::from("Root"), Symbol::from("A")]]),
+    );
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("Z"), /* include_dev */ true),
+        None,
+    );
+}
+
+#[test]
+fn paths_to_dev_deps() {
+    let pkg = dev_dep_test_package();
+    let snapshot = pkg.join("Move.locked");
+
+    let manifest = parse_move_manifest_from_file(&pkg).expect("Loading manifest");
+    let graph = DependencyGraph::read_from_lock(
+        pkg,
+        manifest,
+        &mut File::open(&snapshot).expect("Opening snapshot"),
+    )
+    .expect("Creating DependencyGraph");
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("C"), /* include_dev */ false),
+        Some(vec![vec![Symbol::from("Root"), Symbol::from("C")]]),
+    );
+
+    // B's dev-dependency on C is not built for Root.
+    assert_eq!(
+        graph.paths_to(Symbol::from("C"), /* include_dev */ true),
+        Some(vec![vec![Symbol::from("Root"), Symbol::from("C")]]),
+    );
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("B"), /* include_dev */ false),
+        Some(vec![vec![
+            Symbol::from("Root"),
+            Symbol::from("A"),
+            Symbol::from("B"),
+        ]]),
+    );
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("B"), /* include_dev */ true),
+        Some(vec![
+            vec![Symbol::from("Root"), Symbol::from("A"), Symbol::from("B")],
+            vec![Symbol::from("Root"), Symbol::from("B")],
+        ]),
+    );
+
+    // D is only a dev-dependency of A, so there is no path to it, even with dev-dependencies.
+    assert_eq!(
+        graph.paths_to(Symbol::from("D"), /* include_dev */ false),
+        Some(vec![]),
+    );
+
+    assert_eq!(
+        graph.paths_to(Symbol::from("D"), /* include_dev */ true),
+        Some(vec![]),
+    );
+}
+
+#[test]
+fn locked_resolution_matches_full_resolution() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pkg = tmp.path().join("pkg");
+    copy_dir(&diamond_test_package(), &pkg);
+    fs::copy(pkg.join("Move.locked"), pkg.join("Move.lock")).expect("Committing lock file");
+
+    let resolve = |locked| {
+        BuildConfig {
+            dev_mode: true,
+            install_dir: Some(tmp.path().join("build")),
+            locked,
+            ..Default::default()
+        }
+        .lock_for_package(&pkg, &mut std::io::sink())
+        .expect("Resolving package")
+    };
+
+    let committed = fs::read_to_string(pkg.join("Move.lock")).expect("Reading lock file");
+    assert_eq!(committed, resolve(true), "Lock-only resolution");
+    assert_eq!(committed, resolve(false), "Full resolution");
+}
+
+#[test]
+fn respect_dep_locks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pkg = tmp.path().join("pkg");
+    copy_dir(&dep_lock_pins_test_package(), &pkg);
+
+    let manifest = parse_move_manifest_from_file(&pkg).expect("Loading manifest");
+    let source_of_c = |respect_dep_locks| {
+        let graph = DependencyGraph::new(
+            &manifest,
+            pkg.clone(),
+            /* skip_fetch_latest_git_deps */ true,
+            respect_dep_locks,
+            &mut std::io::sink(),
+        )
+        .expect("Creating DependencyGraph");
+
+        graph.package_sources().remove(&Symbol::from("C"))
+    };
+
+    assert_eq!(
+        source_of_c(false),
+        Some(DependencyKind::Local(PathBuf::from("deps_only/C"))),
+    );
+
+    // A's lock file pins C to a different source than A's manifest
+    assert_eq!(
+        source_of_c(true),
+        Some(DependencyKind::Local(PathBuf::from("deps_only/C_pinned"))),
+    );
+
+    // The pin is recorded in the root package's lock file, and the pinned package is the one that
+    // gets resolved.
+    let lock_path = tmp.path().join("Move.lock");
+    let resolved = BuildConfig {
+        dev_mode: true,
+        install_dir: Some(tmp.path().join("build")),
+        lock_file: Some(lock_path.clone()),
+        respect_dep_locks: true,
+        ..Default::default()
+    }
+    .resolution_graph_for_package(&pkg, &mut std::io::sink())
+    .expect("Resolving package");
+
+    let lock = fs::read_to_string(lock_path).expect("Reading lock file");
+    assert!(
+        lock.contains("source = { local = \"deps_only/C_pinned\" }"),
+        "{}",
+        lock,
+    );
+
+    let c_path = &resolved.package_table[&Symbol::from("C")].package_path;
+    assert!(c_path.ends_with("C_pinned"), "{}", c_path.display());
+}
+
+#[test]
+fn frozen() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pkg = tmp.path().join("pkg");
+    copy_dir(&multiple_deps_test_package(), &pkg);
+
+    // Start from a manifest without one of the dependencies
+    let manifest_path = pkg.join("Move.toml");
+    let manifest = fs::read_to_string(&manifest_path).expect("Reading manifest");
+    let without_d = manifest.replace("D = { local = \"./deps_only/D\" }\n", "");
+    assert_ne!(manifest, without_d);
+    fs::write(&manifest_path, without_d).expect("Writing manifest");
+
+    let lock_path = pkg.join("Move.lock");
+    let resolve = |frozen, locked| {
+        BuildConfig {
+            install_dir: Some(tmp.path().join("build")),
+            lock_file: Some(lock_path.clone()),
+            frozen,
+            locked,
+            ..Default::default()
+        }
+        .resolution_graph_for_package(&pkg, &mut std::io::sink())
+    };
+
+    assert!(resolve(true, false).is_err(), "No lock file to freeze");
+    resolve(false, false).expect("Resolving package");
+    let committed = fs::read_to_string(&lock_path).expect("Reading lock file");
+    resolve(true, false).expect("Resolving package with an up-to-date lock file");
+
+    // The manifest gains a dependency, which would add it to the lock file
+    fs::write(&manifest_path, manifest).expect("Writing manifest");
+
+    let err = resolve(true, false).unwrap_err();
+    assert!(err.to_string().contains("--frozen"), "{:#}", err);
+    assert!(err.to_string().contains("+ D = "), "{:#}", err);
+
+    let err = resolve(false, true).unwrap_err();
+    assert!(err.to_string().contains("--locked"), "{:#}", err);
+
+    let current = fs::read_to_string(&lock_path).expect("Reading lock file");
+    assert_eq!(committed, current);
+}
+
+#[test]
+fn frozen_lock_file_elsewhere() {
+    let tmp = tempfile::tempdir().unwrap();
+    let pkg = tmp.path().join("pkg");
+    copy_dir(&multiple_deps_test_package(), &pkg);
+
+    // The lock file is saved outside the package, which has no lock file of its own
+    let lock_path = tmp.path().join("Move.lock");
+    assert!(!pkg.join("Move.lock").exists());
+    let resolve = |frozen| {
+        BuildConfig {
+            install_dir: Some(tmp.path().join("build")),
+            lock_file: Some(lock_path.clone()),
+            frozen,
+            ..Default::default()
+        }
+        .resolution_graph_for_package(&pkg, &mut std::io::sink())
+    };
+
+    assert!(resolve(true).is_err(), "No lock file to freeze");
+    resolve(false).expect("Resolving package");
+    resolve(true).expect("Resolving package with an up-to-date lock file");
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let to = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &to);
+        } else {
+            fs::copy(entry.path(), to).unwrap();
+        }
+    }
+}
+
 fn one_dep_test_package() -> PathBuf {
     [".", "tests", "test_sources", "one_dep"]
         .into_iter()
         .collect()
 }
 
+fn multiple_deps_test_package() -> PathBuf {
+    [".", "tests", "test_sources", "multiple_deps_no_rename"]
+        .into_iter()
+        .collect()
+}
+
 fn dev_dep_test_package() -> PathBuf {
     [".", "tests", "test_sources", "dep_dev_dep_diamond"]
         .into_iter()
         .collect()
 }
+
+fn dep_lock_pins_test_package() -> PathBuf {
+    [".", "tests", "test_sources", "dep_lock_pins"]
+        .into_iter()
+        .collect()
+}
+
+fn diamond_test_package() -> PathBuf {
+    [".", "tests", "test_sources", "diamond_problem_no_conflict"]
+        .into_iter()
+        .collect()
+}
@@ -1,14 +1,24 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use named_lock::NamedLock;
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs::{self, File},
     io::{self, Read, Write},
     path::PathBuf,
+    thread,
+    time::Duration,
 };
 use tempfile::TempDir;
+use toml::Value;
 
-use move_package::resolution::lock_file::LockFile;
+use move_package::resolution::lock_file::{
+    commit_lock_name,
+    diff::{LockDiff, LockedDependency, Repin},
+    graph::LockGraph,
+    schema, verify_digests, DigestMismatch, LockFile,
+};
 
 #[test]
 fn commit() {
@@ -39,6 +49,91 @@ fn commit() {
     );
 }
 
+#[test]
+fn commit_waits_for_lock() {
+    let pkg = create_test_package().unwrap();
+    let lock_path = pkg.path().join("Move.lock");
+
+    // Hold the lock serializing commits to the lock file...
+    let commit_lock = NamedLock::create(&commit_lock_name(&lock_path).unwrap()).unwrap();
+    let guard = commit_lock.lock().unwrap();
+
+    let committer = {
+        let root = pkg.path().to_path_buf();
+        let lock_path = lock_path.clone();
+        thread::spawn(move || {
+            let mut lock = LockFile::new(&root).unwrap();
+            writeln!(lock, "# Write and commit").unwrap();
+            lock.commit(&lock_path).unwrap();
+        })
+    };
+
+    // ...which blocks the commit until it is released.
+    thread::sleep(Duration::from_millis(500));
+    assert!(!committer.is_finished());
+    assert!(!lock_path.exists());
+
+    drop(guard);
+    committer.join().unwrap();
+    let lock_contents = fs::read_to_string(&lock_path).unwrap();
+    assert!(
+        lock_contents.ends_with("# Write and commit\n"),
+        "Lock file doesn't have expected content:\n{}",
+        lock_contents,
+    );
+}
+
+#[test]
+fn add_dependencies() {
+    let pkg = create_test_package().unwrap();
+    let lock_path = pkg.path().join("Move.lock");
+
+    let dep = |name: &str| schema::Dependency {
+        name: name.to_string(),
+        source: toml::from_str(&format!("local = \"./deps/{}\"", name)).unwrap(),
+        digest: None,
+        dependencies: None,
+        dev_dependencies: None,
+    };
+
+    {
+        let mut lock = LockFile::new(pkg.path()).unwrap();
+        lock.add_dependency(&dep("B"));
+        lock.add_dependency(&dep("A"));
+        lock.finalize().unwrap();
+        // Finalizing again (with nothing added) updates the existing checksum
+        lock.finalize().unwrap();
+        lock.commit(&lock_path).unwrap();
+    }
+
+    let lock = fs::read_to_string(&lock_path).unwrap();
+    assert_eq!(lock.matches("checksum = ").count(), 1, "{}", lock);
+    assert!(schema::verify_checksum(&mut lock.as_bytes()).unwrap());
+
+    let (deps, _) = schema::Dependencies::read(&mut lock.as_bytes()).unwrap();
+    assert_eq!(deps, vec![dep("A"), dep("B")]);
+}
+
+#[test]
+fn stale() {
+    let pkg = create_test_package().unwrap();
+    let manifest_path = pkg.path().join("Move.toml");
+
+    // No lock file yet
+    assert!(LockFile::is_stale(&manifest_path).unwrap());
+
+    LockFile::new(pkg.path())
+        .unwrap()
+        .commit(pkg.path().join("Move.lock"))
+        .unwrap();
+    assert!(!LockFile::is_stale(&manifest_path).unwrap());
+
+    let mut manifest = fs::read_to_string(&manifest_path).unwrap();
+    manifest.push_str("\n[dependencies]\nDep = { local = \"./dep\" }\n");
+    fs::write(&manifest_path, manifest).unwrap();
+    assert!(LockFile::is_stale(&manifest_path).unwrap());
+}
+
 #[test]
 fn discard() {
     let pkg = create_test_package().unwrap();
@@ -51,6 +146,318 @@ fn discard() {
     assert!(!pkg.path().join("Move.lock").is_file());
 }
 
+#[test]
+fn checksum() {
+    let snapshot: PathBuf = [".", "tests", "test_sources", "one_dep", "Move.locked"]
+        .into_iter()
+        .collect();
+    let lock = fs::read_to_string(snapshot).unwrap();
+    assert!(schema::verify_checksum(&mut lock.as_bytes()).unwrap());
+
+    // Editing the dependencies without updating the checksum is detected...
+    let tampered = lock.replace("deps_only/other_dep", "deps_only/other_dep_fork");
+    assert_ne!(lock, tampered);
+    assert!(!schema::verify_checksum(&mut tampered.as_bytes()).unwrap());
+
+    // ...but still readable.
+    let (deps, _) = schema::Dependencies::read(&mut tampered.as_bytes()).unwrap();
+    assert_eq!(deps.len(), 1);
+
+    // Lock files without a checksum are not verified.
+    let unchecked = fs::read_to_string(diff_lock_path("old.lock")).unwrap();
+    assert!(schema::verify_checksum(&mut unchecked.as_bytes()).unwrap());
+}
+
+#[test]
+fn write_round_trip() {
+    let local = |path: &str| {
+        let mut source = toml::value::Table::new();
+        source.insert("local".to_string(), Value::String(path.to_string()));
+        Value::Table(source)
+    };
+
+    let deps = vec![
+        schema::Dependency {
+            name: "A".to_string(),
+            source: local("deps_only/A"),
+            digest: Some("0123456789ABCDEF".to_string()),
+            dependencies: Some(vec!["B".to_string(), "C".to_string()]),
+            dev_dependencies: Some(vec!["D".to_string()]),
+        },
+        schema::Dependency {
+            name: "B".to_string(),
+            source: local("deps_only/B"),
+            digest: None,
+            dependencies: None,
+            dev_dependencies: None,
+        },
+        schema::Dependency {
+            name: "C".to_string(),
+            source: local("deps_only/C"),
+            digest: None,
+            dependencies: None,
+            dev_dependencies: Some(vec!["D".to_string()]),
+        },
+        schema::Dependency {
+            name: "D".to_string(),
+            source: local("deps_only/D"),
+            digest: None,
+            dependencies: None,
+            dev_dependencies: None,
+        },
+    ];
+
+    let mut lock = vec![];
+    schema::Dependencies::new(deps.clone())
+        .write(&mut lock)
+        .unwrap();
+
+    assert!(schema::verify_checksum(&mut lock.as_slice()).unwrap());
+    assert_eq!(
+        schema::Dependencies::read(&mut lock.as_slice()).unwrap().0,
+        deps
+    );
+}
+
+#[test]
+fn compiler_version() {
+    // Lock files record the version of the compiler that wrote them...
+    let current: PathBuf = [".", "tests", "test_sources", "one_dep", "Move.locked"]
+        .into_iter()
+        .collect();
+    let (_, version) = schema::Dependencies::read(&mut File::open(current).unwrap()).unwrap();
+    assert_eq!(version.as_deref(), Some(schema::COMPILER_VERSION));
+
+    // ...but lock files written before it was recorded can still be read.
+    let old = diff_lock_path("old.lock");
+    let (deps, version) = schema::Dependencies::read(&mut File::open(old).unwrap()).unwrap();
+    assert_eq!(version, None);
+    assert!(!deps.is_empty());
+}
+
+#[test]
+fn digest() {
+    let pkg: PathBuf = [".", "tests", "test_sources", "one_dep"]
+        .into_iter()
+        .collect();
+    let lock = fs::read_to_string(pkg.join("Move.locked")).unwrap();
+
+    // Local dependencies record the digest of their sources...
+    let (deps, _) = schema::Dependencies::read(&mut lock.as_bytes()).unwrap();
+    let digest = deps[0]
+        .digest
+        .clone()
+        .expect("Local dependency has a digest");
+    assert!(verify_digests(&mut lock.as_bytes(), &pkg)
+        .unwrap()
+        .is_empty());
+
+    // ...which detects changes to those sources.
+    let tampered = lock.replace(&digest, "0123456789ABCDEF");
+    let mismatches = verify_digests(&mut tampered.as_bytes(), &pkg).unwrap();
+    assert_eq!(
+        mismatches,
+        vec![DigestMismatch {
+            name: "OtherDep".to_string(),
+            expected: "0123456789ABCDEF".to_string(),
+            actual: digest,
+        }]
+    );
+
+    // Lock files without digests are not verified.
+    let unchecked = fs::read_to_string(diff_lock_path("old.lock")).unwrap();
+    let (deps, _) = schema::Dependencies::read(&mut unchecked.as_bytes()).unwrap();
+    assert!(deps.iter().all(|dep| dep.digest.is_none()));
+    assert!(verify_digests(&mut unchecked.as_bytes(), &pkg)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn unsupported_version() {
+    let lock = "[move]\nversion = 999\n";
+    let err = schema::Dependencies::read(&mut lock.as_bytes()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<schema::LockError>(),
+        Some(&schema::LockError::UnsupportedVersion {
+            found: 999,
+            supported: schema::VERSION,
+        })
+    );
+}
+
+#[test]
+fn partition() {
+    let lock = r#"
+        [move]
+        version = 0
+
+        [[move.dependency]]
+        name = "A"
+        source = { local = "deps_only/A" }
+        dependencies = ["B", "C"]
+        dev-dependencies = ["C", "D"]
+
+        [[move.dependency]]
+        name = "B"
+        source = { local = "deps_only/B" }
+        dev-dependencies = ["D"]
+
+        [[move.dependency]]
+        name = "C"
+        source = { local = "deps_only/C" }
+
+        [[move.dependency]]
+        name = "D"
+        source = { local = "deps_only/D" }
+    "#;
+
+    let (deps, _) = schema::Dependencies::read(&mut lock.as_bytes()).unwrap();
+    let edges = schema::Dependencies::partition(&deps);
+
+    let names = |names: &[&str]| -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    };
+
+    // A dev-dependency that is also a dependency (C) is only reported as a dependency.
+    assert_eq!(
+        edges.dependencies,
+        BTreeMap::from([("A".to_string(), names(&["B", "C"]))])
+    );
+    assert_eq!(
+        edges.dev_dependencies,
+        BTreeMap::from([
+            ("A".to_string(), names(&["D"])),
+            ("B".to_string(), names(&["D"])),
+        ])
+    );
+}
+
+#[test]
+fn diff() {
+    let diff = LockDiff::new(
+        &mut File::open(diff_lock_path("old.lock")).unwrap(),
+        &mut File::open(diff_lock_path("new.lock")).unwrap(),
+    )
+    .unwrap();
+
+    // Changes to the dependencies of a dependency are not reported, only changes to its source.
+    let expected = "\
+        + E = { local = \"deps_only/E\" }\n\
+        - D = { local = \"deps_only/D\" }\n\
+        ~ B repinned in https://github.com/move-language/move.git: 0000000 -> 1111111\n\
+        ~ C source changed:\n    \
+            - { addr_subst = { CA = \"00000000000000000000000000000001\" }, local = \"deps_only/C\" }\n    \
+            + { addr_subst = { CA = \"00000000000000000000000000000002\" }, local = \"deps_only/C\" }\n";
+    assert_eq!(diff.to_string(), expected);
+
+    let names = |deps: &[LockedDependency]| -> Vec<String> {
+        deps.iter().map(|dep| dep.name.clone()).collect()
+    };
+    assert_eq!(names(&diff.added), vec!["E"]);
+    assert_eq!(names(&diff.removed), vec!["D"]);
+    assert_eq!(
+        diff.repinned,
+        vec![Repin {
+            name: "B".to_string(),
+            git: "https://github.com/move-language/move.git".to_string(),
+            old_rev: "0000000".to_string(),
+            new_rev: "1111111".to_string(),
+        }]
+    );
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].name, "C");
+}
+
+#[test]
+fn diff_identical() {
+    let lock_path = diff_lock_path("old.lock");
+    let diff = LockDiff::new(
+        &mut File::open(&lock_path).unwrap(),
+        &mut File::open(&lock_path).unwrap(),
+    )
+    .unwrap();
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "No dependency changes\n");
+}
+
+#[test]
+fn graph() {
+    let lock_path: PathBuf = [".", "tests", "test_lock_graph", "Move.lock"]
+        .into_iter()
+        .collect();
+    let graph = LockGraph::new(&mut File::open(lock_path).unwrap()).unwrap();
+
+    assert_eq!(graph.roots(), vec!["A"]);
+
+    let dot = graph.to_dot();
+    for edge in [
+        "\"A\" -> \"B\";",
+        "\"A\" -> \"C\";",
+        "\"A\" -> \"D\" [style = dashed];",
+        "\"B\" -> \"C\";",
+    ] {
+        assert!(dot.contains(edge), "{} missing from:\n{}", edge, dot);
+    }
+
+    let expected = "\
+        A\n  \
+          B\n    \
+            C\n  \
+          C\n  \
+          D (dev)\n";
+    assert_eq!(graph.to_string(), expected);
+}
+
+#[test]
+fn graph_cycle() {
+    let lock = r#"
+        [move]
+        version = 0
+
+        [[move.dependency]]
+        name = "A"
+        source = { local = "deps_only/A" }
+        dependencies = ["B"]
+
+        [[move.dependency]]
+        name = "B"
+        source = { local = "deps_only/B" }
+        dev-dependencies = ["A"]
+    "#;
+
+    let err = LockGraph::new(&mut lock.as_bytes()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Dependency cycle in lock file: A -> B -> A"
+    );
+}
+
+#[test]
+fn unresolved_reference() {
+    let lock = r#"
+        [move]
+        version = 0
+
+        [[move.dependency]]
+        name = "A"
+        source = { local = "deps_only/A" }
+        dependencies = ["B"]
+        dev-dependencies = ["Missing"]
+
+        [[move.dependency]]
+        name = "B"
+        source = { local = "deps_only/B" }
+    "#;
+
+    let err = schema::Dependencies::read(&mut lock.as_bytes()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Dependency 'Missing' of 'A' is missing from the lock file"
+    );
+}
+
 /// Create a simple Move package with no sources (just a manifest and an output directory) in a
 /// temporary directory, and return it.
 fn create_test_package() -> io::Result<TempDir> {
@@ -63,3 +470,8 @@ fn create_test_package() -> io::Result<TempDir> {
     fs::copy(toml_path, dir.path().join("Move.toml"))?;
     Ok(dir)
 }
+
+/// Path to one of the lock files used to test lock file diffs.
+fn diff_lock_path(name: &str) -> PathBuf {
+    [".", "tests", "test_lock_diff", name].into_iter().collect()
+}
@@ -0,0 +1,6 @@
+module B::OneDep {
+    use B::B;
+    public fun do_b() {
+        B::foo()
+    }
+}
@@ -0,0 +1,3 @@
+module std::Dep {
+    public fun foo() { }
+}
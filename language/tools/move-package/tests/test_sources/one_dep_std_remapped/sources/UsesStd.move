@@ -0,0 +1,6 @@
+module std::UsesStd {
+    use std::Dep;
+    public fun do_foo() {
+        Dep::foo()
+    }
+}
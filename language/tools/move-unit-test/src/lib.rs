@@ -37,7 +37,8 @@ pub struct UnitTestingConfig {
     #[clap(name = "gas_limit", short = 'i', long = "gas_limit")]
     pub gas_limit: Option<u64>,
 
-    /// A filter string to determine which unit tests to run
+    /// A filter string to determine which unit tests to run (or list). A unit test is run only if
+    /// its fully qualified (<addr>::<module_name>::<fn_name>) name contains this string
     #[clap(name = "filter", short = 'f', long = "filter")]
     pub filter: Option<String>,
 
@@ -218,13 +219,18 @@ impl UnitTestingConfig {
     /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
     pub fn run_and_report_unit_tests<W: Write + Send>(
         &self,
-        test_plan: TestPlan,
+        mut test_plan: TestPlan,
         native_function_table: Option<NativeFunctionTable>,
         cost_table: Option<CostTable>,
         writer: W,
     ) -> Result<(W, bool)> {
         let shared_writer = Mutex::new(writer);
 
+        let filtered_out = match &self.filter {
+            Some(filter_str) => test_runner::filter_tests(&mut test_plan, filter_str),
+            None => 0,
+        };
+
         if self.list {
             for (module_id, test_plan) in &test_plan.module_tests {
                 for test_name in test_plan.tests.keys() {
@@ -240,7 +246,14 @@ impl UnitTestingConfig {
         }
 
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
-        let mut test_runner = TestRunner::new(
+        if self.filter.is_some() {
+            writeln!(
+                shared_writer.lock().unwrap(),
+                "{} tests filtered out",
+                filtered_out
+            )?;
+        }
+        let test_runner = TestRunner::new(
             self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
             self.num_threads,
             self.check_stackless_vm,
@@ -257,10 +270,6 @@ impl UnitTestingConfig {
         )
         .unwrap();
 
-        if let Some(filter_str) = &self.filter {
-            test_runner.filter(filter_str)
-        }
-
         let test_results = test_runner.run(&shared_writer).unwrap();
         if self.report_statistics {
             test_results.report_statistics(&shared_writer)?;
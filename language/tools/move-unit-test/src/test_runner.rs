@@ -211,23 +211,26 @@ impl TestRunner {
             })
     }
 
-    pub fn filter(&mut self, test_name_slice: &str) {
-        for (module_id, module_test) in self.tests.module_tests.iter_mut() {
-            if module_id.name().as_str().contains(test_name_slice) {
-                continue;
-            } else {
-                let tests = std::mem::take(&mut module_test.tests);
-                module_test.tests = tests
-                    .into_iter()
-                    .filter(|(test_name, _)| {
-                        let full_name =
-                            format!("{}::{}", module_id.name().as_str(), test_name.as_str());
-                        full_name.contains(test_name_slice)
-                    })
-                    .collect();
-            }
-        }
+    /// Only run the tests whose fully qualified names contain `test_name_slice`, returning the
+    /// number of tests that will not run.
+    pub fn filter(&mut self, test_name_slice: &str) -> usize {
+        filter_tests(&mut self.tests, test_name_slice)
+    }
+}
+
+/// Remove the tests from `test_plan` whose fully qualified names (`<addr>::<module>::<fn>`) do not
+/// contain `test_name_slice`, returning the number of tests removed.
+pub fn filter_tests(test_plan: &mut TestPlan, test_name_slice: &str) -> usize {
+    let mut removed = 0;
+    for (module_id, module_test) in test_plan.module_tests.iter_mut() {
+        let module_name = format_module_id(module_id);
+        let before = module_test.tests.len();
+        module_test.tests.retain(|test_name, _| {
+            format!("{}::{}", module_name, test_name).contains(test_name_slice)
+        });
+        removed += before - module_test.tests.len();
     }
+    removed
 }
 
 // TODO: do not expose this to backend implementations